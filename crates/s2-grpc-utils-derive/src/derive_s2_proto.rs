@@ -1,17 +1,17 @@
-use darling::{ast, FromDeriveInput, FromField};
+use darling::{ast, FromDeriveInput, FromField, FromMeta};
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
+use std::collections::HashMap;
+use syn::spanned::Spanned;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 enum InputType {
+  #[default]
   Pack,
   Unpack,
-}
-
-impl Default for InputType {
-  fn default() -> InputType {
-    InputType::Pack
-  }
+  UnpackRef,
+  Diff,
+  Schema,
 }
 
 #[derive(Debug, FromDeriveInput)]
@@ -22,16 +22,104 @@ pub struct InputReceiver {
   ident: syn::Ident,
   generics: syn::Generics,
   data: ast::Data<(), FieldReceiver>,
-  message_type: syn::Path,
+  #[darling(default)]
+  message_type: Option<syn::Path>,
+  #[darling(default = "crate::default_crate_path", rename = "crate")]
+  crate_path: syn::Path,
 }
 
 impl InputReceiver {
-  pub fn to_unpack(self) -> Self {
+  pub fn into_unpack(self) -> Self {
     Self {
       input_type: InputType::Unpack,
       ..self
     }
   }
+
+  pub fn into_unpack_ref(self) -> Self {
+    Self {
+      input_type: InputType::UnpackRef,
+      ..self
+    }
+  }
+
+  pub fn into_diff(self) -> Self {
+    Self {
+      input_type: InputType::Diff,
+      ..self
+    }
+  }
+
+  pub fn into_schema(self) -> Self {
+    Self {
+      input_type: InputType::Schema,
+      ..self
+    }
+  }
+
+  /// Checks the attributes which can not be validated by darling alone
+  pub fn validate(self) -> darling::Result<Self> {
+    let mut errors = vec![];
+
+    let requires_message_type = match self.input_type {
+      InputType::Pack | InputType::Unpack | InputType::UnpackRef => true,
+      InputType::Diff | InputType::Schema => false,
+    };
+    if requires_message_type && self.message_type.is_none() {
+      errors.push(
+        darling::Error::custom(
+          "Missing message type, specify it with `#[s2_grpc(message_type = \"path::to::Message\")]`",
+        )
+        .with_span(&self.ident),
+      );
+    }
+
+    let fields = self
+      .data
+      .as_ref()
+      .take_struct()
+      .expect("Should never be enum")
+      .fields;
+    let mut mapped: HashMap<String, &syn::Ident> = HashMap::new();
+    for f in fields {
+      let field_ident = f.ident.as_ref().unwrap();
+      if f.timestamp.is_some() && f.duration.is_some() {
+        errors.push(
+          darling::Error::custom("`timestamp` and `duration` can not be used together")
+            .with_span(field_ident),
+        );
+      }
+      if let Some(map_fn) = f.map_fn.as_ref() {
+        if f.timestamp.is_some() || f.duration.is_some() {
+          errors.push(
+            darling::Error::custom("`map_fn` can not be used with `timestamp` or `duration`")
+              .with_span(map_fn),
+          );
+        }
+      }
+      let message_field_name = f.message_field_name();
+      if let Some(prev) = mapped.get(&message_field_name) {
+        let message = format!(
+          "Field `{}` maps to message field `{}` which is already mapped by field `{}`",
+          field_ident, message_field_name, prev
+        );
+        let error = darling::Error::custom(message);
+        errors.push(if let Some(rename) = f.rename.as_ref() {
+          error.with_span(rename)
+        } else {
+          error.with_span(field_ident)
+        });
+      } else {
+        mapped.insert(message_field_name, field_ident);
+      }
+    }
+
+    if errors.is_empty() {
+      Ok(self)
+    } else {
+      Err(darling::Error::multiple(errors))
+    }
+  }
 }
 
 impl ToTokens for InputReceiver {
@@ -42,6 +130,7 @@ impl ToTokens for InputReceiver {
       ref generics,
       ref data,
       ref message_type,
+      crate_path: ref krate,
     } = *self;
 
     let (imp, ty, wher) = generics.split_for_impl();
@@ -53,29 +142,39 @@ impl ToTokens for InputReceiver {
 
     match input_type {
       InputType::Pack => {
+        let message_type = message_type.as_ref().expect("Validated");
         let pack_lines: Vec<_> = fields
           .iter()
           .map(|f| {
             let field_ident = &f.ident;
-            let value_field_ident = if let Some(ident) = f.rename.as_ref() {
-              ident
-            } else {
-              f.ident.as_ref().unwrap()
-            };
+            let field_ty = &f.ty;
+            let value_field_ident = f.message_field_ident();
             if let Some(map_fn) = f.map_fn.as_ref() {
-              quote! {
+              quote_spanned! {map_fn.span()=>
                 #value_field_ident: #map_fn(value.#field_ident),
               }
+            } else if let Some((encoding, proto_ty)) = f.encoding(krate) {
+              let wrap_err = field_value_error(krate, field_ident);
+              let pack_fn = quote_spanned! {field_ty.span()=>
+                <#encoding as #krate::S2ProtoEncoding<#field_ty, #proto_ty>>::pack
+              };
+              quote! {
+                #value_field_ident: #pack_fn(value.#field_ident).map_err(#wrap_err)?,
+              }
             } else {
+              // Only the trait path is spanned to the field type, so type errors point at it
+              let pack_fn = quote_spanned! {field_ty.span()=>
+                <#field_ty as #krate::S2ProtoPack<_>>::pack
+              };
               quote! {
-                #value_field_ident: value.#field_ident.pack()?,
+                #value_field_ident: #pack_fn(value.#field_ident)?,
               }
             }
           })
           .collect();
         tokens.extend(quote! {
-          impl #imp s2_grpc_utils::S2ProtoPack<#message_type> for #ident #ty #wher {
-            fn pack(self) -> s2_grpc_utils::result::Result<#message_type> {
+          impl #imp #krate::S2ProtoPack<#message_type> for #ident #ty #wher {
+            fn pack(self) -> #krate::result::Result<#message_type> {
               let value = self;
               Ok(#message_type {
                 #(#pack_lines)*
@@ -83,8 +182,8 @@ impl ToTokens for InputReceiver {
             }
           }
 
-          impl #imp s2_grpc_utils::S2ProtoPack<Option<#message_type>> for #ident #ty #wher {
-            fn pack(self) -> s2_grpc_utils::result::Result<Option<#message_type>> {
+          impl #imp #krate::S2ProtoPack<Option<#message_type>> for #ident #ty #wher {
+            fn pack(self) -> #krate::result::Result<Option<#message_type>> {
               let value = self;
               Ok(Some(#message_type {
                 #(#pack_lines)*
@@ -94,24 +193,111 @@ impl ToTokens for InputReceiver {
         })
       }
       InputType::Unpack => {
+        let message_type = message_type.as_ref().expect("Validated");
         let unpack_lines: Vec<_> = fields
           .iter()
           .map(|f| {
             let field_ident = &f.ident;
-            let value_field_ident = if let Some(ident) = f.rename.as_ref() {
-              ident
+            let field_ty = &f.ty;
+            let value_field_ident = f.message_field_ident();
+            let field_expr = if let Some(map_fn) = f.map_fn.as_ref() {
+              quote_spanned! {map_fn.span()=>
+                #map_fn(value.#value_field_ident)
+              }
+            } else if let Some((encoding, proto_ty)) = f.encoding(krate) {
+              let wrap_err = field_value_error(krate, field_ident);
+              let unpack_fn = quote_spanned! {field_ty.span()=>
+                <#encoding as #krate::S2ProtoEncoding<#field_ty, #proto_ty>>::unpack
+              };
+              quote! {
+                #unpack_fn(value.#value_field_ident).map_err(#wrap_err)?
+              }
             } else {
-              f.ident.as_ref().unwrap()
+              let unpack_fn = quote_spanned! {field_ty.span()=>
+                <#field_ty as #krate::S2ProtoUnpack<_>>::unpack
+              };
+              quote! {
+                #unpack_fn(value.#value_field_ident).map_err(|err| {
+                  if let #krate::result::Error::ValueNotPresent = err {
+                    #krate::result::Error::FieldValueNotPresent {
+                      field_name: stringify!(#field_ident),
+                    }
+                  } else {
+                    err
+                  }
+                })?
+              }
             };
+            quote! {
+              #field_ident: #field_expr,
+            }
+          })
+          .collect();
+        tokens.extend(quote! {
+          impl #imp #krate::S2ProtoUnpack<#message_type> for #ident #ty #wher {
+            fn unpack(value: #message_type) -> #krate::result::Result<#ident> {
+              Ok(#ident {
+                #(#unpack_lines)*
+              })
+            }
+          }
+
+          impl #imp #krate::S2ProtoUnpack<Option<#message_type>> for #ident #ty #wher {
+            fn unpack(value: Option<#message_type>) -> #krate::result::Result<#ident> {
+              if let Some(value) = value {
+                Ok(#ident {
+                  #(#unpack_lines)*
+                })
+              } else {
+                Err(#krate::result::Error::ValueNotPresent)
+              }
+            }
+          }
+        })
+      }
+      InputType::UnpackRef => {
+        let message_type = message_type.as_ref().expect("Validated");
+
+        // Borrow from the message for the struct's first lifetime, or a new one if it has none
+        let mut impl_generics = generics.clone();
+        let lifetime = if let Some(def) = generics.lifetimes().next() {
+          def.lifetime.clone()
+        } else {
+          let lifetime = syn::Lifetime::new("'s2_grpc", proc_macro2::Span::call_site());
+          impl_generics
+            .params
+            .insert(0, syn::LifetimeDef::new(lifetime.clone()).into());
+          lifetime
+        };
+        let (imp, _, _) = impl_generics.split_for_impl();
+
+        let unpack_lines: Vec<_> = fields
+          .iter()
+          .map(|f| {
+            let field_ident = &f.ident;
+            let field_ty = &f.ty;
+            let value_field_ident = f.message_field_ident();
             let field_expr = if let Some(map_fn) = f.map_fn.as_ref() {
+              quote_spanned! {map_fn.span()=>
+                #map_fn(&value.#value_field_ident)
+              }
+            } else if let Some((encoding, proto_ty)) = f.encoding(krate) {
+              // The encoded values are integers and strings, unpacking a copy is cheap
+              let wrap_err = field_value_error(krate, field_ident);
+              let unpack_fn = quote_spanned! {field_ty.span()=>
+                <#encoding as #krate::S2ProtoEncoding<#field_ty, #proto_ty>>::unpack
+              };
               quote! {
-                #map_fn(value.#value_field_ident)
+                #unpack_fn(::std::clone::Clone::clone(&value.#value_field_ident)).map_err(#wrap_err)?
               }
             } else {
+              let unpack_fn = quote_spanned! {field_ty.span()=>
+                <#field_ty as #krate::S2ProtoUnpackRef<#lifetime, _>>::unpack_ref
+              };
               quote! {
-                S2ProtoUnpack::unpack(value.#value_field_ident).map_err(|err| {
-                  if let s2_grpc_utils::result::Error::ValueNotPresent = err {
-                    s2_grpc_utils::result::Error::FieldValueNotPresent {
+                #unpack_fn(&value.#value_field_ident).map_err(|err| {
+                  if let #krate::result::Error::ValueNotPresent = err {
+                    #krate::result::Error::FieldValueNotPresent {
                       field_name: stringify!(#field_ident),
                     }
                   } else {
@@ -125,28 +311,102 @@ impl ToTokens for InputReceiver {
             }
           })
           .collect();
+
         tokens.extend(quote! {
-          impl #imp s2_grpc_utils::S2ProtoUnpack<#message_type> for #ident #ty #wher {
-            fn unpack(value: #message_type) -> s2_grpc_utils::result::Result<#ident> {
+          impl #imp #krate::S2ProtoUnpackRef<#lifetime, #message_type> for #ident #ty #wher {
+            fn unpack_ref(value: &#lifetime #message_type) -> #krate::result::Result<Self> {
               Ok(#ident {
                 #(#unpack_lines)*
               })
             }
           }
 
-          impl #imp s2_grpc_utils::S2ProtoUnpack<Option<#message_type>> for #ident #ty #wher {
-            fn unpack(value: Option<#message_type>) -> s2_grpc_utils::result::Result<#ident> {
-              if let Some(value) = value {
+          impl #imp #krate::S2ProtoUnpackRef<#lifetime, Option<#message_type>> for #ident #ty #wher {
+            fn unpack_ref(value: &#lifetime Option<#message_type>) -> #krate::result::Result<Self> {
+              if let Some(value) = value.as_ref() {
                 Ok(#ident {
                   #(#unpack_lines)*
                 })
               } else {
-                Err(s2_grpc_utils::result::Error::ValueNotPresent)
+                Err(#krate::result::Error::ValueNotPresent)
               }
             }
           }
         })
       }
+      InputType::Diff => {
+        let diff_lines: Vec<_> = fields
+          .iter()
+          .map(|f| {
+            let field_ident = &f.ident;
+            let field_ty = &f.ty;
+            let path_name = f.message_field_name();
+            quote_spanned! {field_ty.span()=>
+              <#field_ty as #krate::S2ProtoDiff>::diff_paths(
+                &self.#field_ident,
+                &other.#field_ident,
+                &#krate::join_field_path(path, #path_name),
+                paths,
+              );
+            }
+          })
+          .collect();
+        tokens.extend(quote! {
+          impl #imp #krate::S2ProtoDiff for #ident #ty #wher {
+            fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+              #(#diff_lines)*
+            }
+          }
+        })
+      }
+      InputType::Schema => {
+        let message_name = if let Some(message_type) = message_type.as_ref() {
+          message_type
+            .segments
+            .last()
+            .expect("Message type path should not be empty")
+            .ident
+            .to_string()
+        } else {
+          ident.to_string()
+        };
+        let schema_fields: Vec<_> = fields
+          .iter()
+          .map(|f| {
+            let field_ty = &f.ty;
+            let field_name = f.message_field_name();
+            // Encoded fields have the type of their encoding instead
+            let field_ty = match f.encoding(krate) {
+              Some((_, proto_ty)) => proto_ty,
+              None => quote! { #field_ty },
+            };
+            quote_spanned! {f.ty.span()=>
+              #krate::schema::Field {
+                name: ::std::string::ToString::to_string(#field_name),
+                ty: <#field_ty as #krate::S2ProtoFieldType>::proto_field_type(),
+              },
+            }
+          })
+          .collect();
+        tokens.extend(quote! {
+          impl #imp #krate::S2ProtoSchema for #ident #ty #wher {
+            fn proto_schema() -> #krate::schema::Schema {
+              #krate::schema::Schema::Message(#krate::schema::MessageSchema {
+                name: ::std::string::ToString::to_string(#message_name),
+                fields: vec![
+                  #(#schema_fields)*
+                ],
+              })
+            }
+          }
+
+          impl #imp #krate::S2ProtoFieldType for #ident #ty #wher {
+            fn proto_field_type() -> #krate::schema::FieldType {
+              #krate::schema::FieldType::Message(::std::string::ToString::to_string(#message_name))
+            }
+          }
+        })
+      }
     }
   }
 }
@@ -160,4 +420,99 @@ struct FieldReceiver {
   rename: Option<syn::Ident>,
   #[darling(default)]
   map_fn: Option<syn::Path>,
+  /// Encodes a `DateTime<Utc>` as `int64` seconds or milliseconds, or as an RFC 3339 string
+  #[darling(default)]
+  timestamp: Option<TimestampEncoding>,
+  /// Encodes a `chrono::Duration` as `int64` seconds or milliseconds, or as a string like "1.5s"
+  #[darling(default)]
+  duration: Option<DurationEncoding>,
+}
+
+#[derive(Debug, Clone, Copy, FromMeta)]
+enum TimestampEncoding {
+  #[darling(rename = "secs")]
+  Secs,
+  #[darling(rename = "millis")]
+  Millis,
+  #[darling(rename = "rfc3339")]
+  Rfc3339,
+}
+
+#[derive(Debug, Clone, Copy, FromMeta)]
+enum DurationEncoding {
+  #[darling(rename = "secs")]
+  Secs,
+  #[darling(rename = "millis")]
+  Millis,
+  #[darling(rename = "string")]
+  String,
+}
+
+/// Whether the type is written as `Option<T>`, optional encoded fields need `encoding::Optional`
+fn is_option(ty: &syn::Type) -> bool {
+  match *ty {
+    syn::Type::Path(syn::TypePath {
+      qself: None,
+      ref path,
+    }) => match path.segments.last() {
+      Some(segment) => {
+        segment.ident == "Option"
+          && matches!(segment.arguments, syn::PathArguments::AngleBracketed(_))
+      }
+      None => false,
+    },
+    _ => false,
+  }
+}
+
+/// Closure which reports an error of an encoded field with the field name
+fn field_value_error(krate: &syn::Path, field_ident: &Option<syn::Ident>) -> TokenStream {
+  quote! {
+    |err| #krate::result::Error::FieldValue {
+      field_name: stringify!(#field_ident),
+      source: Box::new(err),
+    }
+  }
+}
+
+impl FieldReceiver {
+  /// Ident of the corresponding field in the message struct, spanned to this field
+  fn message_field_ident(&self) -> syn::Ident {
+    let field_ident = self.ident.as_ref().unwrap();
+    if let Some(ident) = self.rename.as_ref() {
+      let mut ident = ident.clone();
+      ident.set_span(field_ident.span());
+      ident
+    } else {
+      field_ident.clone()
+    }
+  }
+
+  /// `S2ProtoEncoding` implementation and proto type of a field with an encoding attribute
+  fn encoding(&self, krate: &syn::Path) -> Option<(TokenStream, TokenStream)> {
+    let (name, proto_ty) = match (self.timestamp, self.duration) {
+      (Some(TimestampEncoding::Secs), _) => ("TimestampSecs", quote! { i64 }),
+      (Some(TimestampEncoding::Millis), _) => ("TimestampMillis", quote! { i64 }),
+      (Some(TimestampEncoding::Rfc3339), _) => ("TimestampRfc3339", quote! { String }),
+      (None, Some(DurationEncoding::Secs)) => ("DurationSecs", quote! { i64 }),
+      (None, Some(DurationEncoding::Millis)) => ("DurationMillis", quote! { i64 }),
+      (None, Some(DurationEncoding::String)) => ("DurationString", quote! { String }),
+      (None, None) => return None,
+    };
+    let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+    if is_option(&self.ty) {
+      Some((
+        quote! { #krate::encoding::Optional<#krate::encoding::#name> },
+        proto_ty,
+      ))
+    } else {
+      Some((quote! { #krate::encoding::#name }, proto_ty))
+    }
+  }
+
+  /// Name of the corresponding field in the proto message
+  fn message_field_name(&self) -> String {
+    let name = self.message_field_ident().to_string();
+    name.trim_start_matches("r#").to_string()
+  }
 }
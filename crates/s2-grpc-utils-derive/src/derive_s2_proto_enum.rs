@@ -1,89 +1,975 @@
-use darling::{ast, FromDeriveInput, FromVariant};
+use darling::{ast, FromDeriveInput, FromMeta, FromVariant};
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
+use std::collections::HashMap;
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(s2_grpc), supports(enum_unit))]
+#[darling(attributes(s2_grpc), supports(enum_unit, enum_newtype))]
 pub struct InputReceiver {
+  #[darling(skip)]
+  schema: bool,
   ident: syn::Ident,
   generics: syn::Generics,
   data: ast::Data<VariantReceiver, ()>,
-  proto_enum_type: syn::Path,
+  #[darling(default)]
+  proto_enum_type: Option<syn::Path>,
+  #[darling(default = "crate::default_crate_path", rename = "crate")]
+  crate_path: syn::Path,
+  /// Called with the enum name and the discriminant when the fallback variant is substituted
+  #[darling(default)]
+  on_fallback: Option<syn::Path>,
+  /// Match the proto enum without a wildcard, so unmapped proto values fail to compile
+  #[darling(default)]
+  exhaustive: bool,
+  /// Implement `S2ProtoPack<String>` and `S2ProtoUnpack<String>` using the proto variant names
+  #[darling(default)]
+  string: bool,
+  /// Ignore ASCII case when looking up variants by proto variant name
+  #[darling(default)]
+  case_insensitive: bool,
+  /// Proto variant of the zero value which means `None` for `Option<Self>`
+  #[darling(default)]
+  unspecified: Option<syn::Ident>,
+  /// Implement `S2ProtoPack` and `S2ProtoUnpack` for the proto enum type itself
+  #[darling(default)]
+  typed: bool,
+  /// Implement `Display` using the Rust or proto variant names
+  #[darling(default)]
+  display: Option<NameStyle>,
+  /// Implement `FromStr` using the Rust or proto variant names
+  #[darling(default)]
+  from_str: Option<NameStyle>,
+  /// Implement `Serialize` and `Deserialize` using the Rust or proto variant names
+  #[darling(default)]
+  serde: Option<NameStyle>,
+}
+
+/// Which variant names the generated `Display`, `FromStr` and serde impls use
+#[derive(Debug, Clone, Copy, PartialEq, FromMeta)]
+enum NameStyle {
+  #[darling(rename = "rust")]
+  Rust,
+  #[darling(rename = "proto")]
+  Proto,
+}
+
+impl NameStyle {
+  /// `S2ProtoEnum` function which looks up a variant by name in this style
+  fn lookup_fn(self) -> syn::Ident {
+    let name = match self {
+      NameStyle::Rust => "from_variant_name",
+      NameStyle::Proto => "from_proto_variant_name",
+    };
+    syn::Ident::new(name, proc_macro2::Span::call_site())
+  }
+}
+
+impl InputReceiver {
+  pub fn into_schema(self) -> Self {
+    Self {
+      schema: true,
+      ..self
+    }
+  }
+
+  /// Checks the attributes which can not be validated by darling alone
+  pub fn validate(self) -> darling::Result<Self> {
+    let mut errors = vec![];
+
+    if self.proto_enum_type.is_none() {
+      if self.exhaustive {
+        errors.push(
+          darling::Error::custom("`exhaustive` requires `proto_enum_type`").with_span(&self.ident),
+        );
+      }
+      if let Some(ref unspecified) = self.unspecified {
+        errors.push(
+          darling::Error::custom("`unspecified` requires `proto_enum_type`").with_span(unspecified),
+        );
+      }
+      if self.typed {
+        errors.push(
+          darling::Error::custom("`typed` requires `proto_enum_type`").with_span(&self.ident),
+        );
+      }
+    }
+
+    let variants = self
+      .data
+      .as_ref()
+      .take_enum()
+      .expect("Should never be struct");
+    let mut unknown_variant: Option<&syn::Ident> = None;
+    let mut fallback_variant: Option<&syn::Ident> = None;
+    for v in variants.iter() {
+      if v.fallback {
+        if v.unknown {
+          errors.push(
+            darling::Error::custom("Variant can not be both unknown and fallback")
+              .with_span(&v.ident),
+          );
+        }
+        if let Some(prev) = fallback_variant.or(unknown_variant) {
+          errors.push(
+            darling::Error::custom(format!(
+              "Variant `{}` already receives unmapped values",
+              prev
+            ))
+            .with_span(&v.ident),
+          );
+        }
+        fallback_variant = Some(&v.ident);
+      }
+
+      if v.unknown {
+        if v.fields.style != ast::Style::Tuple || v.fields.len() != 1 {
+          errors.push(
+            darling::Error::custom(
+              "Unknown variant should hold the raw value, e.g. `Unrecognized(i32)`",
+            )
+            .with_span(&v.ident),
+          );
+        }
+        if v.rename.is_some() || !v.aliases.is_empty() {
+          errors.push(
+            darling::Error::custom("Unknown variant can not be renamed or aliased")
+              .with_span(&v.ident),
+          );
+        }
+        if let Some(prev) = unknown_variant.or(fallback_variant) {
+          errors.push(
+            darling::Error::custom(format!(
+              "Variant `{}` already receives unmapped values",
+              prev
+            ))
+            .with_span(&v.ident),
+          );
+        }
+        unknown_variant = Some(&v.ident);
+      }
+
+      if self.proto_enum_type.is_some() && v.value.is_some() {
+        errors.push(
+          darling::Error::custom("`value` can not be used with `proto_enum_type`")
+            .with_span(&v.ident),
+        );
+      }
+      if self.proto_enum_type.is_none() {
+        for alias in &v.aliases {
+          errors
+            .push(darling::Error::custom("`alias` requires `proto_enum_type`").with_span(alias));
+        }
+      }
+
+      if !v.unknown && v.fields.style != ast::Style::Unit {
+        errors.push(
+          darling::Error::custom(
+            "Only unit variants can be mapped, mark the variant holding unmapped values with `#[s2_grpc(unknown)]`",
+          )
+          .with_span(&v.ident),
+        );
+      }
+    }
+
+    if self.proto_enum_type.is_none() {
+      // Only values known at expansion time are compared, rustc rejects duplicate discriminants
+      let mut mapped: HashMap<i64, &syn::Ident> = HashMap::new();
+      for v in variants.iter().filter(|v| !v.unknown) {
+        if let Some(value) = v.value.map(i64::from).or(v.discriminant_value) {
+          if let Some(prev) = mapped.get(&value) {
+            errors.push(
+              darling::Error::custom(format!(
+                "Value `{}` is already mapped by variant `{}`",
+                value, prev
+              ))
+              .with_span(&v.ident),
+            );
+          } else {
+            mapped.insert(value, &v.ident);
+          }
+        }
+      }
+    }
+
+    if self.proto_enum_type.is_some() {
+      let mut mapped: HashMap<String, &syn::Ident> = HashMap::new();
+      for v in variants.iter().filter(|v| !v.unknown) {
+        for proto_ident in std::iter::once(v.proto_ident()).chain(v.aliases.iter()) {
+          if let Some(prev) = mapped.get(&proto_ident.to_string()) {
+            errors.push(
+              darling::Error::custom(format!(
+                "Proto variant `{}` is already mapped by variant `{}`",
+                proto_ident, prev
+              ))
+              .with_span(proto_ident),
+            );
+          } else {
+            mapped.insert(proto_ident.to_string(), &v.ident);
+          }
+        }
+      }
+    }
+
+    if let Some(ref unspecified) = self.unspecified {
+      for v in variants.iter().filter(|v| !v.unknown) {
+        if v.proto_ident() == unspecified || v.aliases.contains(unspecified) {
+          errors.push(
+            darling::Error::custom(format!(
+              "Variant `{}` maps to the unspecified value `{}`, which unpacks to `None`",
+              v.ident, unspecified
+            ))
+            .with_span(&v.ident),
+          );
+        }
+      }
+    }
+
+    if self.serde.is_some() && !self.generics.params.is_empty() {
+      errors.push(
+        darling::Error::custom("`serde` can not be used with generic parameters")
+          .with_span(&self.ident),
+      );
+    }
+
+    if let Some(ref on_fallback) = self.on_fallback {
+      if fallback_variant.is_none() {
+        errors.push(
+          darling::Error::custom(
+            "`on_fallback` requires a variant marked with `#[s2_grpc(fallback)]`",
+          )
+          .with_span(on_fallback),
+        );
+      }
+    }
+
+    if errors.is_empty() {
+      Ok(self)
+    } else {
+      Err(darling::Error::multiple(errors))
+    }
+  }
+
+  /// Copies the discriminants of the variants, which darling does not receive. Implicit
+  /// discriminants are computed like rustc: the previous discriminant plus one, starting at zero.
+  pub fn with_discriminants(mut self, data: &syn::Data) -> Self {
+    if let (ast::Data::Enum(ref mut variants), syn::Data::Enum(ref data)) = (&mut self.data, data) {
+      let mut prev: Option<(syn::Expr, Option<i64>)> = None;
+      for (v, variant) in variants.iter_mut().zip(data.variants.iter()) {
+        let (discriminant, value) = match (&variant.discriminant, prev) {
+          (Some((_, expr)), _) => (expr.clone(), int_value(expr)),
+          (None, Some((prev, None))) => (syn::parse_quote! { (#prev) + 1 }, None),
+          (None, Some((_, Some(value)))) => {
+            let value = value + 1;
+            let lit = proc_macro2::Literal::i64_unsuffixed(value);
+            (syn::parse_quote! { #lit }, Some(value))
+          }
+          (None, None) => (syn::parse_quote! { 0 }, Some(0)),
+        };
+        v.discriminant = Some(discriminant.clone());
+        v.discriminant_value = value;
+        prev = Some((discriminant, value));
+      }
+    }
+    self
+  }
+
+  /// Prefix of the value names in the .proto file, the enum name in `SCREAMING_SNAKE_CASE`
+  fn value_name_prefix(&self) -> String {
+    let name = match self.proto_enum_type {
+      Some(ref proto_enum_type) => proto_enum_type
+        .segments
+        .last()
+        .expect("Proto enum type path should not be empty")
+        .ident
+        .to_string(),
+      None => self.ident.to_string(),
+    };
+    to_screaming_snake_case(&name)
+  }
+
+  /// Proto variants known to this enum: the unspecified value, the mapped variants and aliases
+  fn proto_idents(&self) -> impl Iterator<Item = &syn::Ident> {
+    let variants = self
+      .data
+      .as_ref()
+      .take_enum()
+      .expect("Should never be struct");
+    self.unspecified.iter().chain(
+      variants
+        .into_iter()
+        .filter(|v| !v.unknown)
+        .flat_map(|v| std::iter::once(v.proto_ident()).chain(v.aliases.iter())),
+    )
+  }
+
+  fn unknown_variant(&self) -> Option<&VariantReceiver> {
+    self
+      .data
+      .as_ref()
+      .take_enum()
+      .expect("Should never be struct")
+      .into_iter()
+      .find(|v| v.unknown)
+  }
+
+  fn fallback_variant(&self) -> Option<&VariantReceiver> {
+    self
+      .data
+      .as_ref()
+      .take_enum()
+      .expect("Should never be struct")
+      .into_iter()
+      .find(|v| v.fallback)
+  }
+
+  fn schema_tokens(&self) -> TokenStream {
+    let InputReceiver {
+      ref ident,
+      ref generics,
+      ref data,
+      ref proto_enum_type,
+      crate_path: ref krate,
+      ..
+    } = *self;
+
+    let (imp, ty, wher) = generics.split_for_impl();
+    let variants = data.as_ref().take_enum().expect("Should never be struct");
+    let prefix = self.value_name_prefix();
+    let enum_name = if let Some(proto_enum_type) = proto_enum_type.as_ref() {
+      proto_enum_type
+        .segments
+        .last()
+        .expect("Proto enum type path should not be empty")
+        .ident
+        .to_string()
+    } else {
+      ident.to_string()
+    };
+
+    let values: Vec<_> = if let Some(proto_enum_type) = proto_enum_type.as_ref() {
+      let unspecified = self
+        .unspecified
+        .iter()
+        .map(|unspecified| (value_name(&prefix, unspecified), unspecified));
+      let mapped = variants.iter().filter(|v| !v.unknown).flat_map(|v| {
+        std::iter::once((v.proto_name(&prefix), v.proto_ident())).chain(
+          v.aliases
+            .iter()
+            .map(|alias| (value_name(&prefix, alias), alias)),
+        )
+      });
+      unspecified
+        .chain(mapped)
+        .map(|(proto_name, proto_ident)| {
+          quote_spanned! {proto_ident.span()=>
+            #krate::schema::EnumValue {
+              name: ::std::string::ToString::to_string(#proto_name),
+              number: #proto_enum_type::#proto_ident as i32,
+            },
+          }
+        })
+        .collect()
+    } else {
+      variants
+        .iter()
+        .filter(|v| !v.unknown)
+        .map(|v| {
+          let proto_name = v.proto_name(&prefix);
+          let value = v.value_tokens();
+          quote_spanned! {v.ident.span()=>
+            #krate::schema::EnumValue {
+              name: ::std::string::ToString::to_string(#proto_name),
+              number: #value,
+            },
+          }
+        })
+        .collect()
+    };
+
+    quote! {
+      impl #imp #krate::S2ProtoSchema for #ident #ty #wher {
+        fn proto_schema() -> #krate::schema::Schema {
+          #krate::schema::Schema::Enum(#krate::schema::EnumSchema {
+            name: ::std::string::ToString::to_string(#enum_name),
+            values: vec![
+              #(#values)*
+            ],
+          })
+        }
+      }
+
+      impl #imp #krate::S2ProtoFieldType for #ident #ty #wher {
+        fn proto_field_type() -> #krate::schema::FieldType {
+          #krate::schema::FieldType::Enum(::std::string::ToString::to_string(#enum_name))
+        }
+      }
+    }
+  }
+
+  /// Serializes mapped variants as their names and unrecognized values as their discriminants
+  fn serde_tokens(&self, style: NameStyle) -> TokenStream {
+    let ident = &self.ident;
+    let krate = &self.crate_path;
+    let variants = self
+      .data
+      .as_ref()
+      .take_enum()
+      .expect("Should never be struct");
+    let name = ident.to_string();
+    let lookup = style.lookup_fn();
+    let prefix = self.value_name_prefix();
+
+    let serialize_arms: Vec<_> = variants
+      .iter()
+      .map(|v| {
+        let v_ident = &v.ident;
+        if v.unknown {
+          quote! {
+            Self::#v_ident(v) if serializer.is_human_readable() => serializer.serialize_i32(v),
+            Self::#v_ident(v) => serializer.serialize_str(&::std::string::ToString::to_string(&v)),
+          }
+        } else {
+          let v_name = v.style_name(style, &prefix);
+          quote! {
+            Self::#v_ident => serializer.serialize_str(#v_name),
+          }
+        }
+      })
+      .collect();
+
+    let variant_names: Vec<_> = variants
+      .iter()
+      .filter(|v| !v.unknown)
+      .map(|v| v.style_name(style, &prefix))
+      .collect();
+
+    // Formats which are not self-describing can not deserialize numbers or strings, so the
+    // discriminants are written as strings there
+    let (visit_discriminant, deserialize) = if self.unknown_variant().is_some() {
+      (
+        quote! {
+          fn visit_i64<E>(self, v: i64) -> ::std::result::Result<#ident, E>
+          where
+            E: serde::de::Error,
+          {
+            use ::std::convert::TryFrom;
+            i32::try_from(v)
+              .ok()
+              .and_then(<#ident as #krate::S2ProtoEnum>::from_i32)
+              .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+          }
+
+          fn visit_u64<E>(self, v: u64) -> ::std::result::Result<#ident, E>
+          where
+            E: serde::de::Error,
+          {
+            use ::std::convert::TryFrom;
+            i32::try_from(v)
+              .ok()
+              .and_then(<#ident as #krate::S2ProtoEnum>::from_i32)
+              .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+          }
+        },
+        quote! {
+          if deserializer.is_human_readable() {
+            deserializer.deserialize_any(Visitor)
+          } else {
+            deserializer.deserialize_str(Visitor)
+          }
+        },
+      )
+    } else {
+      (quote! {}, quote! { deserializer.deserialize_str(Visitor) })
+    };
+    let parse_discriminant = self.unknown_variant().map(|_| {
+      quote! {
+        .or_else(|| v.parse::<i32>().ok().and_then(<#ident as #krate::S2ProtoEnum>::from_i32))
+      }
+    });
+
+    quote! {
+      const _: () = {
+        use #krate::__private::serde;
+
+        impl serde::Serialize for #ident {
+          fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+          where
+            S: serde::Serializer,
+          {
+            match *self {
+              #(#serialize_arms)*
+            }
+          }
+        }
+
+        impl<'de> serde::Deserialize<'de> for #ident {
+          fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+          where
+            D: serde::Deserializer<'de>,
+          {
+            struct Visitor;
+
+            impl<'de> serde::de::Visitor<'de> for Visitor {
+              type Value = #ident;
+
+              fn expecting(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                write!(f, "a variant of {}", #name)
+              }
+
+              fn visit_str<E>(self, v: &str) -> ::std::result::Result<#ident, E>
+              where
+                E: serde::de::Error,
+              {
+                <#ident as #krate::S2ProtoEnum>::#lookup(v)
+                  #parse_discriminant
+                  .ok_or_else(|| E::unknown_variant(v, &[#(#variant_names),*]))
+              }
+
+              #visit_discriminant
+            }
+
+            #deserialize
+          }
+        }
+      };
+    }
+  }
 }
 
 impl ToTokens for InputReceiver {
   fn to_tokens(&self, tokens: &mut TokenStream) {
+    if self.schema {
+      tokens.extend(self.schema_tokens());
+      return;
+    }
+
     let InputReceiver {
       ref ident,
       ref generics,
       ref data,
       ref proto_enum_type,
+      crate_path: ref krate,
+      ref on_fallback,
+      exhaustive,
+      string,
+      case_insensitive,
+      ref unspecified,
+      typed,
+      display,
+      from_str,
+      serde,
+      ..
     } = *self;
 
     let (imp, ty, wher) = generics.split_for_impl();
     let variants = data.as_ref().take_enum().expect("Should never be struct");
+    let prefix = self.value_name_prefix();
+
+    let (proto_enum, s2p, from_i32_mapped) = if let Some(proto_enum_type) = proto_enum_type {
+      // Compare discriminants instead of calling `from_i32`, which newer prost versions replaced
+      let (s2p, p2s): (Vec<_>, Vec<_>) = variants
+        .iter()
+        .filter(|v| !v.unknown)
+        .map(|v| {
+          let v_ident = &v.ident;
+          let proto_ident = v.proto_ident();
+          let proto_idents = std::iter::once(proto_ident).chain(v.aliases.iter());
+          (
+            quote_spanned! {v_ident.span()=>
+              Self::#v_ident => #proto_enum_type::#proto_ident,
+            },
+            quote_spanned! {v_ident.span()=>
+              v if #(v == #proto_enum_type::#proto_idents as i32)||* => Some(Self::#v_ident),
+            },
+          )
+        })
+        .unzip();
+
+      (
+        quote! { #proto_enum_type },
+        s2p,
+        quote! {
+          match v {
+            #(#p2s)*
+            _ => None,
+          }
+        },
+      )
+    } else {
+      // Without a proto enum the values come from the discriminants or `value` attributes
+      let (s2p, p2s): (Vec<_>, Vec<_>) = variants
+        .iter()
+        .filter(|v| !v.unknown)
+        .map(|v| {
+          let v_ident = &v.ident;
+          let value = v.value_tokens();
+          (
+            quote_spanned! {v_ident.span()=>
+              Self::#v_ident => #value,
+            },
+            quote_spanned! {v_ident.span()=>
+              v if v == #value => Some(Self::#v_ident),
+            },
+          )
+        })
+        .unzip();
+
+      (
+        quote! { i32 },
+        s2p,
+        quote! {
+          match v {
+            #(#p2s)*
+            _ => None,
+          }
+        },
+      )
+    };
 
-    let (s2p, p2s): (Vec<_>, Vec<_>) = variants
+    // Matching the proto enum without a wildcard makes unmapped proto values fail to compile
+    let exhaustive_check = if exhaustive {
+      let proto_enum_type = proto_enum_type.as_ref().expect("Validated");
+      let proto_idents: Vec<_> = self.proto_idents().collect();
+      Some(quote! {
+        const _: () = {
+          #[allow(dead_code)]
+          fn check_exhaustive(p: #proto_enum_type) {
+            match p {
+              #(#proto_enum_type::#proto_idents => {})*
+            }
+          }
+        };
+      })
+    } else {
+      None
+    };
+
+    let names: Vec<_> = variants
       .iter()
       .map(|v| {
         let v_ident = &v.ident;
-        let proto_ident = if let Some(ref ident) = v.rename {
-          ident
+        let v_name = v.ident.to_string();
+        if v.unknown {
+          quote! {
+            Self::#v_ident(_) => #v_name,
+          }
         } else {
-          v_ident
-        };
-        (
           quote! {
-            Self::#v_ident => #proto_enum_type::#proto_ident,
-          },
+            Self::#v_ident => #v_name,
+          }
+        }
+      })
+      .collect();
+
+    let proto_names: Vec<_> = variants
+      .iter()
+      .map(|v| {
+        let v_ident = &v.ident;
+        if v.unknown {
           quote! {
-            #proto_enum_type::#proto_ident => Some(Self::#v_ident),
-          },
-        )
+            Self::#v_ident(_) => None,
+          }
+        } else {
+          let proto_name = v.proto_name(&prefix);
+          quote! {
+            Self::#v_ident => Some(#proto_name),
+          }
+        }
       })
-      .unzip();
+      .collect();
 
-    let names: Vec<_> = variants
+    let from_proto_names: Vec<_> = variants
       .iter()
+      .filter(|v| !v.unknown)
       .map(|v| {
         let v_ident = &v.ident;
-        let v_name = v.ident.to_string();
+        let conditions = std::iter::once(v.proto_name(&prefix))
+          .chain(v.aliases.iter().map(|alias| value_name(&prefix, alias)))
+          .map(|proto_name| {
+            if case_insensitive {
+              quote! { name.eq_ignore_ascii_case(#proto_name) }
+            } else {
+              quote! { name == #proto_name }
+            }
+          });
         quote! {
-          Self::#v_ident => #v_name,
+          if #(#conditions)||* {
+            return Some(Self::#v_ident);
+          }
         }
       })
       .collect();
 
+    let mapped_idents: Vec<_> = variants
+      .iter()
+      .filter(|v| !v.unknown)
+      .map(|v| &v.ident)
+      .collect();
+    let mapped_names: Vec<_> = mapped_idents.iter().map(|i| i.to_string()).collect();
+
     let name = ident.to_string();
 
+    let unspecified_const = unspecified.as_ref().map(|unspecified| {
+      let proto_enum_type = proto_enum_type.as_ref().expect("Validated");
+      quote! {
+        const UNSPECIFIED: Option<i32> = Some(#proto_enum_type::#unspecified as i32);
+      }
+    });
+    let unspecified_impl = unspecified.as_ref().map(|_| {
+      quote! {
+        impl #imp #krate::S2ProtoEnumUnspecified for #ident #ty #wher {}
+      }
+    });
+
+    // Unmapped discriminants are kept by the unknown variant, or replaced by the fallback variant
+    let (from_i32_unmapped, s2p_unknown, to_i32) = if let Some(v) = self.unknown_variant() {
+      let v_ident = &v.ident;
+      if let Some(proto_enum_type) = proto_enum_type {
+        (
+          quote! { Some(Self::#v_ident(v)) },
+          quote! {
+            Self::#v_ident(v) => <#proto_enum_type as ::std::convert::TryFrom<i32>>::try_from(v)
+              .unwrap_or_default(),
+          },
+          quote! {
+            fn to_i32(&self) -> i32
+            where
+              #proto_enum_type: Into<i32>,
+            {
+              match *self {
+                Self::#v_ident(v) => v,
+                _ => #krate::S2ProtoEnum::pack(self).into(),
+              }
+            }
+          },
+        )
+      } else {
+        (
+          quote! { Some(Self::#v_ident(v)) },
+          quote! {
+            Self::#v_ident(v) => v,
+          },
+          quote! {},
+        )
+      }
+    } else if let Some(v) = self.fallback_variant() {
+      let v_ident = &v.ident;
+      let report = on_fallback.as_ref().map(|on_fallback| {
+        quote! {
+          #on_fallback(#name, v);
+        }
+      });
+      (
+        quote! {
+          {
+            #report
+            Some(Self::#v_ident)
+          }
+        },
+        quote! {},
+        quote! {},
+      )
+    } else {
+      (quote! { None }, quote! {}, quote! {})
+    };
+
     tokens.extend(quote! {
-      impl #imp s2_grpc_utils::S2ProtoEnum for #ident #ty  #wher {
-        type ProtoEnum = #proto_enum_type;
+      #unspecified_impl
+
+      impl #imp #krate::S2ProtoEnumVariants for #ident #ty #wher {
+        const ALL: &'static [Self] = &[#(Self::#mapped_idents),*];
+      }
+
+      impl #imp #krate::S2ProtoEnum for #ident #ty  #wher {
+        type ProtoEnum = #proto_enum;
         const NAME: &'static str = #name;
+        #unspecified_const
 
         fn from_i32(v: i32) -> Option<Self> {
-          #proto_enum_type::from_i32(v)
-            .and_then(|p| {
-              match p {
-                #(#p2s)*
-                _ => None,
-              }
-            })
+          let mapped = #from_i32_mapped;
+          if mapped.is_some() {
+            mapped
+          } else {
+            #from_i32_unmapped
+          }
         }
 
-        fn pack(&self) -> #proto_enum_type {
+        fn pack(&self) -> #proto_enum {
           match *self {
             #(#s2p)*
+            #s2p_unknown
           }
         }
 
+        #to_i32
+
         fn get_variant_name(&self) -> &'static str {
           match *self {
             #(#names)*
           }
         }
+
+        fn from_variant_name(name: &str) -> Option<Self> {
+          match name {
+            #(#mapped_names => Some(Self::#mapped_idents),)*
+            _ => None,
+          }
+        }
+
+        fn get_proto_variant_name(&self) -> Option<&'static str> {
+          match *self {
+            #(#proto_names)*
+          }
+        }
+
+        fn from_proto_variant_name(name: &str) -> Option<Self> {
+          #(#from_proto_names)*
+          None
+        }
+      }
+    });
+
+    tokens.extend(exhaustive_check);
+
+    if let Some(proto_enum_type) = proto_enum_type {
+      // Unrecognized values the proto enum does not know either can not be converted
+      let (into_proto, pack_proto) = if let Some(v) = self.unknown_variant() {
+        let v_ident = &v.ident;
+        (
+          quote! {
+            impl #imp ::std::convert::TryFrom<#ident #ty> for #proto_enum_type #wher {
+              type Error = #krate::result::Error;
+
+              fn try_from(value: #ident #ty) -> #krate::result::Result<Self> {
+                match value {
+                  #ident::#v_ident(v) => <#proto_enum_type as ::std::convert::TryFrom<i32>>::try_from(v)
+                    .map_err(|_| #krate::result::Error::EnumDiscriminantNotFound {
+                      enum_name: #name,
+                      discriminant: v,
+                    }),
+                  _ => Ok(#krate::S2ProtoEnum::pack(&value)),
+                }
+              }
+            }
+          },
+          quote! { ::std::convert::TryFrom::try_from(self) },
+        )
+      } else {
+        (
+          quote! {
+            impl #imp ::std::convert::From<#ident #ty> for #proto_enum_type #wher {
+              fn from(value: #ident #ty) -> Self {
+                #krate::S2ProtoEnum::pack(&value)
+              }
+            }
+          },
+          quote! { Ok(#krate::S2ProtoEnum::pack(&self)) },
+        )
+      };
+
+      tokens.extend(quote! {
+        #into_proto
+
+        impl #imp ::std::convert::TryFrom<#proto_enum_type> for #ident #ty #wher {
+          type Error = #krate::result::Error;
+
+          fn try_from(value: #proto_enum_type) -> #krate::result::Result<Self> {
+            <Self as #krate::S2ProtoUnpack<i32>>::unpack(value as i32)
+          }
+        }
+      });
+
+      // Opt-in, as `pack()` calls would otherwise need to name the target type
+      if typed {
+        tokens.extend(quote! {
+          impl #imp #krate::S2ProtoPack<#proto_enum_type> for #ident #ty #wher {
+            fn pack(self) -> #krate::result::Result<#proto_enum_type> {
+              #pack_proto
+            }
+          }
+
+          impl #imp #krate::S2ProtoUnpack<#proto_enum_type> for #ident #ty #wher {
+            fn unpack(value: #proto_enum_type) -> #krate::result::Result<Self> {
+              ::std::convert::TryFrom::try_from(value)
+            }
+          }
+        })
       }
-    })
+    }
+
+    if string {
+      tokens.extend(quote! {
+        impl #imp #krate::S2ProtoPack<String> for #ident #ty #wher {
+          fn pack(self) -> #krate::result::Result<String> {
+            #krate::S2ProtoEnum::get_proto_variant_name(&self)
+              .map(::std::string::ToString::to_string)
+              .ok_or(#krate::result::Error::EnumVariantNameNotPresent { enum_name: #name })
+          }
+        }
+
+        impl #imp #krate::S2ProtoUnpack<String> for #ident #ty #wher {
+          fn unpack(value: String) -> #krate::result::Result<Self> {
+            match <Self as #krate::S2ProtoEnum>::from_proto_variant_name(&value) {
+              Some(v) => Ok(v),
+              None => Err(#krate::result::Error::EnumVariantNameNotFound {
+                enum_name: #name,
+                name: value,
+              }),
+            }
+          }
+        }
+      })
+    }
+
+    if let Some(style) = display {
+      let arms: Vec<_> = variants
+        .iter()
+        .map(|v| {
+          let v_ident = &v.ident;
+          if v.unknown {
+            quote! {
+              Self::#v_ident(v) => write!(f, "{}", v),
+            }
+          } else {
+            let v_name = v.style_name(style, &prefix);
+            quote! {
+              Self::#v_ident => f.write_str(#v_name),
+            }
+          }
+        })
+        .collect();
+      tokens.extend(quote! {
+        impl #imp ::std::fmt::Display for #ident #ty #wher {
+          fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            match *self {
+              #(#arms)*
+            }
+          }
+        }
+      })
+    }
+
+    if let Some(style) = from_str {
+      let lookup = style.lookup_fn();
+      tokens.extend(quote! {
+        impl #imp ::std::str::FromStr for #ident #ty #wher {
+          type Err = #krate::result::Error;
+
+          fn from_str(s: &str) -> #krate::result::Result<Self> {
+            <Self as #krate::S2ProtoEnum>::#lookup(s).ok_or_else(|| {
+              #krate::result::Error::EnumVariantNameNotFound {
+                enum_name: #name,
+                name: ::std::string::ToString::to_string(s),
+              }
+            })
+          }
+        }
+      })
+    }
+
+    if let Some(style) = serde {
+      tokens.extend(self.serde_tokens(style));
+    }
   }
 }
 
@@ -91,6 +977,102 @@ impl ToTokens for InputReceiver {
 #[darling(attributes(s2_grpc))]
 struct VariantReceiver {
   ident: syn::Ident,
+  fields: ast::Fields<()>,
   #[darling(default)]
   rename: Option<syn::Ident>,
+  #[darling(default)]
+  unknown: bool,
+  #[darling(default)]
+  fallback: bool,
+  /// Other proto variants which unpack to this variant, `rename` still decides the packed value
+  #[darling(multiple, rename = "alias")]
+  aliases: Vec<syn::Ident>,
+  /// Value name in the .proto file, for values which do not start with the enum name prefix
+  #[darling(default)]
+  proto_name: Option<String>,
+  /// Value of the variant when there is no proto enum, overrides the discriminant
+  #[darling(default)]
+  value: Option<i32>,
+  #[darling(skip)]
+  discriminant: Option<syn::Expr>,
+  /// Value of `discriminant` when it is an integer literal or follows one
+  #[darling(skip)]
+  discriminant_value: Option<i64>,
+}
+
+impl VariantReceiver {
+  /// Ident of the corresponding variant in the proto enum
+  fn proto_ident(&self) -> &syn::Ident {
+    self.rename.as_ref().unwrap_or(&self.ident)
+  }
+
+  /// Value name of the variant in the .proto file, e.g. `STATUS_PAYMENT_PENDING`
+  fn proto_name(&self, prefix: &str) -> String {
+    match self.proto_name {
+      Some(ref proto_name) => proto_name.clone(),
+      None => value_name(prefix, self.proto_ident()),
+    }
+  }
+
+  /// Name of the variant in the generated `Display`, `FromStr` and serde impls
+  fn style_name(&self, style: NameStyle, prefix: &str) -> String {
+    match style {
+      NameStyle::Rust => self.ident.to_string(),
+      NameStyle::Proto => self.proto_name(prefix),
+    }
+  }
+
+  /// Value of the variant when there is no proto enum
+  fn value_tokens(&self) -> TokenStream {
+    if let Some(value) = self.value {
+      quote! { #value }
+    } else {
+      let discriminant = self
+        .discriminant
+        .as_ref()
+        .expect("Set by with_discriminants");
+      quote! { (#discriminant) as i32 }
+    }
+  }
+}
+
+/// Value of an integer literal discriminant like `404` or `-1`
+fn int_value(expr: &syn::Expr) -> Option<i64> {
+  match *expr {
+    syn::Expr::Lit(syn::ExprLit {
+      lit: syn::Lit::Int(ref lit),
+      ..
+    }) => lit.base10_parse().ok(),
+    syn::Expr::Unary(syn::ExprUnary {
+      op: syn::UnOp::Neg(_),
+      ref expr,
+      ..
+    }) => int_value(expr).map(|value| -value),
+    syn::Expr::Group(syn::ExprGroup { ref expr, .. })
+    | syn::Expr::Paren(syn::ExprParen { ref expr, .. }) => int_value(expr),
+    _ => None,
+  }
+}
+
+/// Value name in the .proto file of a proto variant, prost strips the prefix from `ident`
+fn value_name(prefix: &str, ident: &syn::Ident) -> String {
+  let name = to_screaming_snake_case(&ident.to_string());
+  if name.starts_with(prefix) && name[prefix.len()..].starts_with('_') {
+    name
+  } else {
+    format!("{}_{}", prefix, name)
+  }
+}
+
+fn to_screaming_snake_case(name: &str) -> String {
+  let mut r = String::with_capacity(name.len() + 4);
+  let mut prev_lower = false;
+  for c in name.trim_start_matches("r#").chars() {
+    if c.is_uppercase() && prev_lower {
+      r.push('_');
+    }
+    prev_lower = c.is_lowercase() || c.is_ascii_digit();
+    r.extend(c.to_uppercase());
+  }
+  r
 }
@@ -7,6 +7,12 @@ use syn::{parse_macro_input, DeriveInput};
 mod derive_s2_proto;
 mod derive_s2_proto_enum;
 
+/// Path of the `s2_grpc_utils` crate used by the generated code, override with
+/// `#[s2_grpc(crate = "path")]` when the crate is re-exported by another crate
+fn default_crate_path() -> syn::Path {
+  syn::parse_quote!(s2_grpc_utils)
+}
+
 macro_rules! try_parse {
   ($e:expr) => {
     match $e {
@@ -19,22 +25,58 @@ macro_rules! try_parse {
 #[proc_macro_derive(S2ProtoPack, attributes(s2_grpc))]
 pub fn derive_pack(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
   let input = parse_macro_input!(input as DeriveInput);
-  let receiver = try_parse!(derive_s2_proto::InputReceiver::from_derive_input(&input));
+  let receiver = try_parse!(
+    derive_s2_proto::InputReceiver::from_derive_input(&input).and_then(|r| r.validate())
+  );
   TokenStream::from(quote!(#receiver))
 }
 
 #[proc_macro_derive(S2ProtoUnpack, attributes(s2_grpc))]
 pub fn derive_unpack(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
   let input = parse_macro_input!(input as DeriveInput);
-  let receiver = try_parse!(derive_s2_proto::InputReceiver::from_derive_input(&input)).to_unpack();
+  let receiver = try_parse!(derive_s2_proto::InputReceiver::from_derive_input(&input)
+    .and_then(|r| r.into_unpack().validate()));
   TokenStream::from(quote!(#receiver))
 }
 
+#[proc_macro_derive(S2ProtoUnpackRef, attributes(s2_grpc))]
+pub fn derive_unpack_ref(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  let receiver = try_parse!(derive_s2_proto::InputReceiver::from_derive_input(&input)
+    .and_then(|r| r.into_unpack_ref().validate()));
+  TokenStream::from(quote!(#receiver))
+}
+
+#[proc_macro_derive(S2ProtoDiff, attributes(s2_grpc))]
+pub fn derive_diff(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  let receiver = try_parse!(derive_s2_proto::InputReceiver::from_derive_input(&input)
+    .and_then(|r| r.into_diff().validate()));
+  TokenStream::from(quote!(#receiver))
+}
+
+#[proc_macro_derive(S2ProtoSchema, attributes(s2_grpc))]
+pub fn derive_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  if let syn::Data::Enum(_) = input.data {
+    let receiver = try_parse!(
+      derive_s2_proto_enum::InputReceiver::from_derive_input(&input)
+        .and_then(|r| r.with_discriminants(&input.data).into_schema().validate())
+    );
+    TokenStream::from(quote!(#receiver))
+  } else {
+    let receiver = try_parse!(derive_s2_proto::InputReceiver::from_derive_input(&input)
+      .and_then(|r| r.into_schema().validate()));
+    TokenStream::from(quote!(#receiver))
+  }
+}
+
 #[proc_macro_derive(S2ProtoEnum, attributes(s2_grpc))]
 pub fn derive_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
   let input = parse_macro_input!(input as DeriveInput);
-  let receiver = try_parse!(derive_s2_proto_enum::InputReceiver::from_derive_input(
-    &input
-  ));
+  let receiver = try_parse!(
+    derive_s2_proto_enum::InputReceiver::from_derive_input(&input)
+      .and_then(|r| r.with_discriminants(&input.data).validate())
+  );
   TokenStream::from(quote!(#receiver))
 }
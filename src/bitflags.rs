@@ -0,0 +1,74 @@
+/// Maps a [bitflags](https://docs.rs/bitflags) type to a `repeated` proto enum field (`Vec<i32>`),
+/// one flag per proto enum value.
+///
+/// Flags are packed in the order they are listed, packing a value with flags which are not listed
+/// fails with `FlagsNotMapped`. On unpack, values which are not listed fail with
+/// `EnumDiscriminantNotFound`, and repeated values are merged into a single flag.
+///
+/// ```ignore
+/// s2_grpc_utils::s2_proto_bitflags!(Permissions => proto::Permission {
+///   READ => Read,
+///   WRITE => Write,
+/// });
+/// ```
+#[macro_export]
+macro_rules! s2_proto_bitflags {
+  ($flags:ty => $proto:ty { $($flag:ident => $value:ident),+ $(,)? }) => {
+    impl $crate::S2ProtoPack<Vec<i32>> for $flags {
+      fn pack(self) -> $crate::result::Result<Vec<i32>> {
+        let mut values = vec![];
+        let mut rest = self;
+        $(
+          if self.contains(<$flags>::$flag) {
+            values.push(<$proto>::$value as i32);
+            rest.remove(<$flags>::$flag);
+          }
+        )+
+        if !rest.is_empty() {
+          return Err($crate::result::Error::FlagsNotMapped {
+            flags_name: stringify!($flags),
+            flags: format!("{:?}", rest),
+          });
+        }
+        Ok(values)
+      }
+    }
+
+    impl $crate::S2ProtoUnpack<Vec<i32>> for $flags {
+      fn unpack(value: Vec<i32>) -> $crate::result::Result<$flags> {
+        let mut flags = <$flags>::empty();
+        for v in value {
+          $(
+            if v == <$proto>::$value as i32 {
+              flags.insert(<$flags>::$flag);
+              continue;
+            }
+          )+
+          return Err($crate::result::Error::EnumDiscriminantNotFound {
+            enum_name: stringify!($proto),
+            discriminant: v,
+          });
+        }
+        Ok(flags)
+      }
+    }
+
+    impl $crate::S2ProtoDiff for $flags {
+      fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+        if self != other {
+          paths.push(path.to_string());
+        }
+      }
+    }
+
+    impl $crate::S2ProtoFieldType for $flags {
+      fn proto_field_type() -> $crate::schema::FieldType {
+        let path = stringify!($proto);
+        let name = path.rsplit("::").next().unwrap_or(path).trim();
+        $crate::schema::FieldType::Repeated(Box::new($crate::schema::FieldType::Enum(
+          name.to_string(),
+        )))
+      }
+    }
+  };
+}
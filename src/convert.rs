@@ -1,16 +1,17 @@
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, Utc};
-use prost_types;
 use prost_types::{Timestamp, Value};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use snafu::ResultExt;
+use std::borrow::Cow;
 use std::cmp::Eq;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::hash::Hash;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::result::{self, Result};
-use crate::{S2ProtoEnum, S2ProtoPack, S2ProtoUnpack};
+use crate::{S2ProtoEnum, S2ProtoEnumUnspecified, S2ProtoPack, S2ProtoUnpack, S2ProtoUnpackRef};
 
 macro_rules! impl_option {
   ($rust:ty => $proto:ty) => {
@@ -32,6 +33,23 @@ macro_rules! impl_option {
   };
 }
 
+/// Borrowed unpacking of values converted from small proto messages, by unpacking a copy
+macro_rules! impl_clone_ref {
+  ($rust:ty => $proto:ty) => {
+    impl<'a> $crate::S2ProtoUnpackRef<'a, $proto> for $rust {
+      fn unpack_ref(value: &'a $proto) -> Result<$rust> {
+        <$rust>::unpack(value.clone())
+      }
+    }
+
+    impl<'a> $crate::S2ProtoUnpackRef<'a, Option<$proto>> for $rust {
+      fn unpack_ref(value: &'a Option<$proto>) -> Result<$rust> {
+        <$rust>::unpack(value.clone())
+      }
+    }
+  };
+}
+
 // JSON value
 
 impl S2ProtoPack<Value> for JsonValue {
@@ -171,62 +189,202 @@ where
   T: for<'de> Deserialize<'de>,
 {
   let value = JsonValue::unpack(value)?;
-  Ok(serde_json::from_value(value).context(result::Json)?)
+  serde_json::from_value(value).context(result::Json)
 }
 
 // Timestamp
 
-impl S2ProtoPack<Timestamp> for DateTime<Utc> {
-  fn pack(self) -> Result<Timestamp> {
-    Ok(Timestamp {
-      seconds: self.timestamp(),
-      nanos: self.timestamp_subsec_nanos() as i32,
-    })
+/// Seconds of 0001-01-01T00:00:00Z, the minimum `google.protobuf.Timestamp`
+const MIN_TIMESTAMP_SECONDS: i64 = -62_135_596_800;
+/// Seconds of 9999-12-31T23:59:59Z, the maximum `google.protobuf.Timestamp`
+const MAX_TIMESTAMP_SECONDS: i64 = 253_402_300_799;
+pub(crate) const NANOS_PER_SECOND: i32 = 1_000_000_000;
+
+/// Carries `nanos` outside of `0..1e9` into `seconds` and checks the `google.protobuf.Timestamp` range
+pub(crate) fn normalize_timestamp(seconds: i64, nanos: i32) -> Result<(i64, u32)> {
+  let invalid = |reason| result::Error::InvalidTimestamp {
+    seconds,
+    nanos,
+    reason,
+  };
+  let normalized_seconds = seconds
+    .checked_add(i64::from(nanos.div_euclid(NANOS_PER_SECOND)))
+    .ok_or_else(|| invalid("seconds overflow"))?;
+  if !(MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&normalized_seconds) {
+    return Err(invalid(
+      "out of range 0001-01-01T00:00:00Z to 9999-12-31T23:59:59.999999999Z",
+    ));
   }
+  Ok((
+    normalized_seconds,
+    nanos.rem_euclid(NANOS_PER_SECOND) as u32,
+  ))
 }
 
-impl S2ProtoUnpack<Timestamp> for DateTime<Utc> {
-  fn unpack(Timestamp { seconds, nanos }: Timestamp) -> Result<DateTime<Utc>> {
-    let dt = chrono::NaiveDateTime::from_timestamp(seconds, nanos as u32);
-    Ok(DateTime::from_utc(dt, Utc))
-  }
+// Duration
+
+/// Seconds of 10,000 years, the maximum magnitude of `google.protobuf.Duration`
+const MAX_DURATION_SECONDS: i64 = 315_576_000_000;
+const MAX_DURATION_NANOS: i32 = NANOS_PER_SECOND - 1;
+
+/// Checks the `google.protobuf.Duration` range and that `seconds` and `nanos` have matching signs
+pub(crate) fn validate_duration(seconds: i64, nanos: i32) -> Result<()> {
+  let reason = if !(-MAX_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&seconds) {
+    "seconds out of range -315576000000 to 315576000000"
+  } else if !(-MAX_DURATION_NANOS..=MAX_DURATION_NANOS).contains(&nanos) {
+    "nanos out of range -999999999 to 999999999"
+  } else if (seconds < 0 && nanos > 0) || (seconds > 0 && nanos < 0) {
+    "seconds and nanos have different signs"
+  } else {
+    return Ok(());
+  };
+  Err(result::Error::InvalidDuration {
+    seconds,
+    nanos,
+    reason,
+  })
 }
 
-// Duration
+// google.type
+
+/// Checks that all fields of a `google.type.Date` are set and in range, but not the calendar
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub(crate) fn validate_full_date(date: &crate::google_type::Date) -> Result<()> {
+  let reason = if date.year == 0 || date.month == 0 || date.day == 0 {
+    "partial dates are not supported"
+  } else if !(1..=9999).contains(&date.year) {
+    "year out of range 1 to 9999"
+  } else if !(1..=12).contains(&date.month) {
+    "month out of range 1 to 12"
+  } else if !(1..=31).contains(&date.day) {
+    "day out of range 1 to 31"
+  } else {
+    return Ok(());
+  };
+  Err(invalid_date(date, reason))
+}
 
-impl S2ProtoPack<prost_types::Duration> for chrono::Duration {
-  fn pack(self) -> Result<prost_types::Duration> {
-    let duration =
-      <prost_types::Duration as From<std::time::Duration>>::from(self.to_std().map_err(|e| {
-        result::Error::ParseDuration {
-          message: e.to_string(),
-        }
-      })?);
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub(crate) fn invalid_date(date: &crate::google_type::Date, reason: &'static str) -> result::Error {
+  result::Error::InvalidDate {
+    year: date.year,
+    month: date.month,
+    day: date.day,
+    reason,
+  }
+}
 
-    Ok(duration)
+/// Checks the `google.type.TimeOfDay` ranges, which allow `24:00:00` and leap second `60`
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub(crate) fn validate_time_of_day(time: &crate::google_type::TimeOfDay) -> Result<()> {
+  let reason = if !(0..=24).contains(&time.hours) {
+    "hours out of range 0 to 24"
+  } else if time.hours == 24 && (time.minutes != 0 || time.seconds != 0 || time.nanos != 0) {
+    "hours can only be 24 for 24:00:00"
+  } else if !(0..=59).contains(&time.minutes) {
+    "minutes out of range 0 to 59"
+  } else if !(0..=60).contains(&time.seconds) {
+    "seconds out of range 0 to 60"
+  } else if !(0..NANOS_PER_SECOND).contains(&time.nanos) {
+    "nanos out of range 0 to 999999999"
+  } else {
+    return Ok(());
+  };
+  Err(invalid_time_of_day(time, reason))
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub(crate) fn invalid_time_of_day(
+  time: &crate::google_type::TimeOfDay,
+  reason: &'static str,
+) -> result::Error {
+  result::Error::InvalidTimeOfDay {
+    hours: time.hours,
+    minutes: time.minutes,
+    seconds: time.seconds,
+    nanos: time.nanos,
+    reason,
   }
 }
 
-impl S2ProtoUnpack<prost_types::Duration> for chrono::Duration {
-  fn unpack(value: prost_types::Duration) -> Result<chrono::Duration> {
-    use std::convert::TryInto;
+// std::time
 
-    let std_duration = <prost_types::Duration as TryInto<std::time::Duration>>::try_into(value)
-      .map_err(|e| result::Error::ParseDuration {
-        message: format!(
-          "Source duration value is out of range for the target type {}",
-          e.as_secs().to_string()
-        ),
-      })?;
+impl S2ProtoPack<prost_types::Duration> for std::time::Duration {
+  fn pack(self) -> Result<prost_types::Duration> {
+    // Saturated seconds are rejected by the range check
+    let seconds = i64::try_from(self.as_secs()).unwrap_or(i64::MAX);
+    let nanos = self.subsec_nanos() as i32;
+    validate_duration(seconds, nanos)?;
+    Ok(prost_types::Duration { seconds, nanos })
+  }
+}
 
-    chrono::Duration::from_std(std_duration).map_err(|e| result::Error::ParseDuration {
-      message: e.to_string(),
+impl S2ProtoUnpack<prost_types::Duration> for std::time::Duration {
+  fn unpack(
+    prost_types::Duration { seconds, nanos }: prost_types::Duration,
+  ) -> Result<std::time::Duration> {
+    validate_duration(seconds, nanos)?;
+    if seconds < 0 || nanos < 0 {
+      return Err(result::Error::InvalidDuration {
+        seconds,
+        nanos,
+        reason: "negative durations can not be converted to std::time::Duration",
+      });
+    }
+    Ok(std::time::Duration::new(seconds as u64, nanos as u32))
+  }
+}
+
+impl S2ProtoPack<Timestamp> for SystemTime {
+  fn pack(self) -> Result<Timestamp> {
+    // Saturated seconds are rejected by the range check
+    let (seconds, nanos) = match self.duration_since(UNIX_EPOCH) {
+      Ok(since) => (
+        i64::try_from(since.as_secs()).unwrap_or(i64::MAX),
+        since.subsec_nanos() as i32,
+      ),
+      Err(e) => {
+        let before = e.duration();
+        (
+          -i64::try_from(before.as_secs()).unwrap_or(i64::MAX),
+          -(before.subsec_nanos() as i32),
+        )
+      }
+    };
+    let (seconds, nanos) = normalize_timestamp(seconds, nanos)?;
+    Ok(Timestamp {
+      seconds,
+      nanos: nanos as i32,
     })
   }
 }
 
-impl_option!(DateTime<Utc> => Timestamp);
-impl_option!(chrono::Duration => prost_types::Duration);
+impl S2ProtoUnpack<Timestamp> for SystemTime {
+  fn unpack(Timestamp { seconds, nanos }: Timestamp) -> Result<SystemTime> {
+    let (normalized_seconds, normalized_nanos) = normalize_timestamp(seconds, nanos)?;
+    let time = if normalized_seconds >= 0 {
+      UNIX_EPOCH.checked_add(std::time::Duration::from_secs(normalized_seconds as u64))
+    } else {
+      UNIX_EPOCH.checked_sub(std::time::Duration::from_secs(
+        normalized_seconds.unsigned_abs(),
+      ))
+    };
+    time
+      .and_then(|time| {
+        time.checked_add(std::time::Duration::from_nanos(u64::from(normalized_nanos)))
+      })
+      .ok_or(result::Error::InvalidTimestamp {
+        seconds,
+        nanos,
+        reason: "not representable by SystemTime on this platform",
+      })
+  }
+}
+
+impl_option!(std::time::Duration => prost_types::Duration);
+impl_option!(SystemTime => Timestamp);
+impl_clone_ref!(std::time::Duration => prost_types::Duration);
+impl_clone_ref!(SystemTime => Timestamp);
 
 // BigDecimal
 
@@ -278,6 +436,66 @@ impl_self! {
   String
 }
 
+// Borrowed values
+
+macro_rules! impl_copy_ref {
+  (
+    $($ty:ty),*
+  ) => {
+    $(
+      impl<'a> S2ProtoUnpackRef<'a, $ty> for $ty {
+        fn unpack_ref(value: &'a $ty) -> Result<$ty> {
+          Ok(*value)
+        }
+      }
+    )*
+  }
+}
+
+impl_copy_ref! {
+  f32,
+  f64,
+  i64,
+  u64,
+  i32,
+  u32,
+  bool
+}
+
+impl<'a, T> S2ProtoUnpackRef<'a, T> for &'a T {
+  fn unpack_ref(value: &'a T) -> Result<&'a T> {
+    Ok(value)
+  }
+}
+
+impl<'a> S2ProtoUnpackRef<'a, String> for &'a str {
+  fn unpack_ref(value: &'a String) -> Result<&'a str> {
+    Ok(value.as_str())
+  }
+}
+
+impl<'a> S2ProtoUnpackRef<'a, String> for Cow<'a, str> {
+  fn unpack_ref(value: &'a String) -> Result<Cow<'a, str>> {
+    Ok(Cow::Borrowed(value.as_str()))
+  }
+}
+
+/// Also covers `bytes` fields (`Vec<u8>` => `&[u8]`)
+impl<'a, T> S2ProtoUnpackRef<'a, Vec<T>> for &'a [T] {
+  fn unpack_ref(value: &'a Vec<T>) -> Result<&'a [T]> {
+    Ok(value.as_slice())
+  }
+}
+
+impl<'a, T> S2ProtoUnpackRef<'a, Vec<T>> for Cow<'a, [T]>
+where
+  T: Clone,
+{
+  fn unpack_ref(value: &'a Vec<T>) -> Result<Cow<'a, [T]>> {
+    Ok(Cow::Borrowed(value.as_slice()))
+  }
+}
+
 // repeated value
 
 impl<T, T2> S2ProtoPack<Vec<T>> for Vec<T2>
@@ -314,6 +532,23 @@ where
   }
 }
 
+impl<'a, T, T2> S2ProtoUnpackRef<'a, Vec<T>> for Vec<T2>
+where
+  T2: S2ProtoUnpackRef<'a, T>,
+{
+  fn unpack_ref(value: &'a Vec<T>) -> Result<Vec<T2>> {
+    let mut r = Vec::with_capacity(value.len());
+    for (i, elem) in value.iter().enumerate() {
+      let item = T2::unpack_ref(elem).map_err(|e| result::Error::ListElement {
+        source: Box::new(e),
+        index: i,
+      })?;
+      r.push(item);
+    }
+    Ok(r)
+  }
+}
+
 // map
 
 impl<K, V, K2, V2> S2ProtoPack<HashMap<K, V>> for HashMap<K2, V2>
@@ -358,6 +593,27 @@ where
   }
 }
 
+impl<'a, K, V, K2, V2> S2ProtoUnpackRef<'a, HashMap<K, V>> for HashMap<K2, V2>
+where
+  K: Eq + Hash,
+  K2: S2ProtoUnpackRef<'a, K> + Eq + Hash,
+  V2: S2ProtoUnpackRef<'a, V>,
+{
+  fn unpack_ref(value: &'a HashMap<K, V>) -> Result<HashMap<K2, V2>> {
+    let mut r = HashMap::with_capacity(value.len());
+    for (k, v) in value.iter() {
+      let k2 = K2::unpack_ref(k).map_err(|e| result::Error::MapEntry {
+        source: Box::new(e),
+      })?;
+      let v2 = V2::unpack_ref(v).map_err(|e| result::Error::MapEntry {
+        source: Box::new(e),
+      })?;
+      r.insert(k2, v2);
+    }
+    Ok(r)
+  }
+}
+
 // Enum
 
 impl<T> S2ProtoPack<i32> for T
@@ -366,8 +622,7 @@ where
   <T as S2ProtoEnum>::ProtoEnum: Into<i32>,
 {
   fn pack(self) -> Result<i32> {
-    let v = <Self as S2ProtoEnum>::pack(&self);
-    Ok(v.into())
+    Ok(<Self as S2ProtoEnum>::to_i32(&self))
   }
 }
 
@@ -376,9 +631,47 @@ where
   T: S2ProtoEnum,
 {
   fn unpack(v: i32) -> Result<T> {
-    <Self as S2ProtoEnum>::from_i32(v).ok_or_else(|| result::Error::EnumDiscriminantNotFound {
+    if T::UNSPECIFIED == Some(v) {
+      return Err(result::Error::ValueNotPresent);
+    }
+    <Self as S2ProtoEnum>::from_i32(v).ok_or(result::Error::EnumDiscriminantNotFound {
       enum_name: T::NAME,
       discriminant: v,
     })
   }
 }
+
+impl<T> S2ProtoPack<i32> for Option<T>
+where
+  T: S2ProtoEnumUnspecified,
+  <T as S2ProtoEnum>::ProtoEnum: Into<i32>,
+{
+  fn pack(self) -> Result<i32> {
+    match self {
+      Some(v) => Ok(v.to_i32()),
+      None => T::UNSPECIFIED.ok_or(result::Error::EnumUnspecifiedNotDefined { enum_name: T::NAME }),
+    }
+  }
+}
+
+impl<T> S2ProtoUnpack<i32> for Option<T>
+where
+  T: S2ProtoEnumUnspecified,
+{
+  fn unpack(v: i32) -> Result<Option<T>> {
+    if T::UNSPECIFIED == Some(v) {
+      Ok(None)
+    } else {
+      T::unpack(v).map(Some)
+    }
+  }
+}
+
+impl<'a, T> S2ProtoUnpackRef<'a, i32> for T
+where
+  T: S2ProtoEnum,
+{
+  fn unpack_ref(v: &'a i32) -> Result<T> {
+    T::unpack(*v)
+  }
+}
@@ -0,0 +1,581 @@
+use chrono::{
+  DateTime, Datelike, FixedOffset, Month, NaiveDate, NaiveDateTime, NaiveTime, TimeZone as _,
+  Timelike, Utc, Weekday,
+};
+use prost_types::Timestamp;
+use std::ops::{Range, RangeFrom, RangeTo};
+
+use crate::convert::{
+  invalid_date, invalid_time_of_day, normalize_timestamp, validate_duration, validate_full_date,
+  validate_time_of_day, NANOS_PER_SECOND,
+};
+use crate::google_type::date_time::TimeOffset;
+use crate::google_type::{Date, DayOfWeek, Interval, Month as ProtoMonth, TimeOfDay};
+use crate::result::{self, Result};
+use crate::{S2ProtoEnum, S2ProtoEnumUnspecified, S2ProtoEnumVariants, S2ProtoPack, S2ProtoUnpack};
+
+// Timestamp
+
+impl S2ProtoPack<Timestamp> for DateTime<Utc> {
+  fn pack(self) -> Result<Timestamp> {
+    // Leap seconds are represented with nanos >= 1e9 and carried into the next second
+    let (seconds, nanos) =
+      normalize_timestamp(self.timestamp(), self.timestamp_subsec_nanos() as i32)?;
+    Ok(Timestamp {
+      seconds,
+      nanos: nanos as i32,
+    })
+  }
+}
+
+impl S2ProtoUnpack<Timestamp> for DateTime<Utc> {
+  fn unpack(Timestamp { seconds, nanos }: Timestamp) -> Result<DateTime<Utc>> {
+    let (normalized_seconds, normalized_nanos) = normalize_timestamp(seconds, nanos)?;
+    Utc
+      .timestamp_opt(normalized_seconds, normalized_nanos)
+      .single()
+      .ok_or(result::Error::InvalidTimestamp {
+        seconds,
+        nanos,
+        reason: "not representable",
+      })
+  }
+}
+
+impl_option!(DateTime<Utc> => Timestamp);
+impl_clone_ref!(DateTime<Utc> => Timestamp);
+
+// Duration
+
+impl S2ProtoPack<prost_types::Duration> for chrono::Duration {
+  fn pack(self) -> Result<prost_types::Duration> {
+    // Both parts are truncated towards zero, so they have the same sign
+    let seconds = self.num_seconds();
+    let nanos = (self - chrono::Duration::seconds(seconds))
+      .num_nanoseconds()
+      .expect("Sub-second duration should fit in nanoseconds") as i32;
+    validate_duration(seconds, nanos)?;
+    Ok(prost_types::Duration { seconds, nanos })
+  }
+}
+
+impl S2ProtoUnpack<prost_types::Duration> for chrono::Duration {
+  fn unpack(
+    prost_types::Duration { seconds, nanos }: prost_types::Duration,
+  ) -> Result<chrono::Duration> {
+    validate_duration(seconds, nanos)?;
+    Ok(chrono::Duration::seconds(seconds) + chrono::Duration::nanoseconds(i64::from(nanos)))
+  }
+}
+
+impl_option!(chrono::Duration => prost_types::Duration);
+impl_clone_ref!(chrono::Duration => prost_types::Duration);
+
+// Date
+
+impl S2ProtoPack<Date> for NaiveDate {
+  fn pack(self) -> Result<Date> {
+    let date = Date {
+      year: self.year(),
+      month: self.month() as i32,
+      day: self.day() as i32,
+    };
+    validate_full_date(&date)?;
+    Ok(date)
+  }
+}
+
+impl S2ProtoUnpack<Date> for NaiveDate {
+  fn unpack(value: Date) -> Result<NaiveDate> {
+    validate_full_date(&value)?;
+    NaiveDate::from_ymd_opt(value.year, value.month as u32, value.day as u32)
+      .ok_or_else(|| invalid_date(&value, "invalid calendar date"))
+  }
+}
+
+impl_option!(NaiveDate => Date);
+impl_clone_ref!(NaiveDate => Date);
+
+/// Whole or partial `google.type.Date`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PartialDate {
+  /// Year, month and day
+  Full(NaiveDate),
+  /// Year and month without day, e.g. a credit card expiration date
+  YearMonth { year: i32, month: u32 },
+  /// Year alone
+  Year(i32),
+  /// Month and day without year, e.g. an anniversary
+  MonthDay { month: u32, day: u32 },
+}
+
+impl S2ProtoPack<Date> for PartialDate {
+  fn pack(self) -> Result<Date> {
+    let date = match self {
+      PartialDate::Full(date) => return date.pack(),
+      PartialDate::YearMonth { year, month } => Date {
+        year,
+        month: month as i32,
+        day: 0,
+      },
+      PartialDate::Year(year) => Date {
+        year,
+        month: 0,
+        day: 0,
+      },
+      PartialDate::MonthDay { month, day } => Date {
+        year: 0,
+        month: month as i32,
+        day: day as i32,
+      },
+    };
+    PartialDate::unpack(date.clone())?;
+    Ok(date)
+  }
+}
+
+impl S2ProtoUnpack<Date> for PartialDate {
+  fn unpack(value: Date) -> Result<PartialDate> {
+    let Date { year, month, day } = value;
+    if year != 0 && !(1..=9999).contains(&year) {
+      return Err(invalid_date(&value, "year out of range 1 to 9999"));
+    }
+    if month != 0 && !(1..=12).contains(&month) {
+      return Err(invalid_date(&value, "month out of range 1 to 12"));
+    }
+    match (year, month, day) {
+      (0, 0, _) => Err(invalid_date(
+        &value,
+        "a date needs at least a year or a month and a day",
+      )),
+      (_, 0, _) if day != 0 => Err(invalid_date(&value, "day without month")),
+      (_, 0, _) => Ok(PartialDate::Year(year)),
+      (_, _, 0) if year == 0 => Err(invalid_date(&value, "month without year or day")),
+      (_, _, 0) => Ok(PartialDate::YearMonth {
+        year,
+        month: month as u32,
+      }),
+      (0, _, _) => {
+        // Any year validates the day, a leap year also allows February 29
+        NaiveDate::from_ymd_opt(2000, month as u32, day as u32)
+          .ok_or_else(|| invalid_date(&value, "invalid calendar date"))?;
+        Ok(PartialDate::MonthDay {
+          month: month as u32,
+          day: day as u32,
+        })
+      }
+      _ => NaiveDate::unpack(value).map(PartialDate::Full),
+    }
+  }
+}
+
+impl_option!(PartialDate => Date);
+impl_clone_ref!(PartialDate => Date);
+
+// Time of day
+
+impl S2ProtoPack<TimeOfDay> for NaiveTime {
+  fn pack(self) -> Result<TimeOfDay> {
+    let nanos = self.nanosecond() as i32;
+    // chrono represents a leap second as second 59 with nanos >= 1e9
+    let (seconds, nanos) = if nanos >= NANOS_PER_SECOND {
+      (60, nanos - NANOS_PER_SECOND)
+    } else {
+      (self.second() as i32, nanos)
+    };
+    Ok(TimeOfDay {
+      hours: self.hour() as i32,
+      minutes: self.minute() as i32,
+      seconds,
+      nanos,
+    })
+  }
+}
+
+impl S2ProtoUnpack<TimeOfDay> for NaiveTime {
+  fn unpack(value: TimeOfDay) -> Result<NaiveTime> {
+    validate_time_of_day(&value)?;
+    if value.hours == 24 {
+      return Err(invalid_time_of_day(
+        &value,
+        "24:00:00 can not be represented by NaiveTime",
+      ));
+    }
+    let (seconds, nanos) = if value.seconds == 60 {
+      (59, value.nanos + NANOS_PER_SECOND)
+    } else {
+      (value.seconds, value.nanos)
+    };
+    NaiveTime::from_hms_nano_opt(
+      value.hours as u32,
+      value.minutes as u32,
+      seconds as u32,
+      nanos as u32,
+    )
+    .ok_or_else(|| invalid_time_of_day(&value, "not representable"))
+  }
+}
+
+impl_option!(NaiveTime => TimeOfDay);
+impl_clone_ref!(NaiveTime => TimeOfDay);
+
+/// `google.type.TimeOfDay` including the end of day value `24:00:00`, e.g. for closing hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeOrEndOfDay {
+  Time(NaiveTime),
+  /// `24:00:00`, after every `Time`
+  EndOfDay,
+}
+
+impl S2ProtoPack<TimeOfDay> for TimeOrEndOfDay {
+  fn pack(self) -> Result<TimeOfDay> {
+    match self {
+      TimeOrEndOfDay::Time(time) => time.pack(),
+      TimeOrEndOfDay::EndOfDay => Ok(TimeOfDay {
+        hours: 24,
+        minutes: 0,
+        seconds: 0,
+        nanos: 0,
+      }),
+    }
+  }
+}
+
+impl S2ProtoUnpack<TimeOfDay> for TimeOrEndOfDay {
+  fn unpack(value: TimeOfDay) -> Result<TimeOrEndOfDay> {
+    validate_time_of_day(&value)?;
+    if value.hours == 24 {
+      Ok(TimeOrEndOfDay::EndOfDay)
+    } else {
+      NaiveTime::unpack(value).map(TimeOrEndOfDay::Time)
+    }
+  }
+}
+
+impl_option!(TimeOrEndOfDay => TimeOfDay);
+impl_clone_ref!(TimeOrEndOfDay => TimeOfDay);
+
+// Date time
+
+/// Fields of a `google.type.DateTime` without time offset
+pub(crate) fn pack_naive_date_time(value: NaiveDateTime) -> Result<crate::google_type::DateTime> {
+  let Date { year, month, day } = value.date().pack()?;
+  let TimeOfDay {
+    hours,
+    minutes,
+    seconds,
+    nanos,
+  } = value.time().pack()?;
+  Ok(crate::google_type::DateTime {
+    year,
+    month,
+    day,
+    hours,
+    minutes,
+    seconds,
+    nanos,
+    time_offset: None,
+  })
+}
+
+/// Local date time of a `google.type.DateTime`, ignoring its time offset
+pub(crate) fn unpack_naive_date_time(
+  value: &crate::google_type::DateTime,
+) -> Result<NaiveDateTime> {
+  let date = NaiveDate::unpack(Date {
+    year: value.year,
+    month: value.month,
+    day: value.day,
+  })?;
+  let time = NaiveTime::unpack(TimeOfDay {
+    hours: value.hours,
+    minutes: value.minutes,
+    seconds: value.seconds,
+    nanos: value.nanos,
+  })?;
+  Ok(date.and_time(time))
+}
+
+/// The maximum UTC offset `google.type.DateTime` allows, 18 hours
+const MAX_UTC_OFFSET_SECONDS: i64 = 18 * 3600;
+
+/// Checks a `utc_offset` against the range and precision `google.type.DateTime` allows
+fn unpack_utc_offset(offset: &prost_types::Duration) -> Result<FixedOffset> {
+  let invalid = |reason| result::Error::InvalidTimeOffset { reason };
+  if offset.nanos != 0 {
+    return Err(invalid("utc_offset must be whole seconds"));
+  }
+  if !(-MAX_UTC_OFFSET_SECONDS..=MAX_UTC_OFFSET_SECONDS).contains(&offset.seconds) {
+    return Err(invalid("utc_offset out of range -18 to +18 hours"));
+  }
+  FixedOffset::east_opt(offset.seconds as i32)
+    .ok_or_else(|| invalid("utc_offset out of range -18 to +18 hours"))
+}
+
+impl S2ProtoPack<crate::google_type::DateTime> for NaiveDateTime {
+  fn pack(self) -> Result<crate::google_type::DateTime> {
+    pack_naive_date_time(self)
+  }
+}
+
+impl S2ProtoUnpack<crate::google_type::DateTime> for NaiveDateTime {
+  fn unpack(value: crate::google_type::DateTime) -> Result<NaiveDateTime> {
+    if value.time_offset.is_some() {
+      return Err(result::Error::InvalidTimeOffset {
+        reason: "local date time can not have a UTC offset or time zone",
+      });
+    }
+    unpack_naive_date_time(&value)
+  }
+}
+
+impl S2ProtoPack<crate::google_type::DateTime> for DateTime<FixedOffset> {
+  fn pack(self) -> Result<crate::google_type::DateTime> {
+    let offset = prost_types::Duration {
+      seconds: i64::from(self.offset().local_minus_utc()),
+      nanos: 0,
+    };
+    // chrono allows offsets up to a day, which `google.type.DateTime` does not
+    unpack_utc_offset(&offset)?;
+    Ok(crate::google_type::DateTime {
+      time_offset: Some(TimeOffset::UtcOffset(offset)),
+      ..pack_naive_date_time(self.naive_local())?
+    })
+  }
+}
+
+impl S2ProtoUnpack<crate::google_type::DateTime> for DateTime<FixedOffset> {
+  fn unpack(value: crate::google_type::DateTime) -> Result<DateTime<FixedOffset>> {
+    let invalid = |reason| result::Error::InvalidTimeOffset { reason };
+    let offset = match value.time_offset {
+      Some(TimeOffset::UtcOffset(ref offset)) => offset,
+      Some(TimeOffset::TimeZone(_)) => {
+        return Err(invalid("time zone can not be converted to a fixed offset"))
+      }
+      None => return Err(invalid("missing utc_offset")),
+    };
+    let offset = unpack_utc_offset(offset)?;
+    let local = unpack_naive_date_time(&value)?;
+    offset
+      .from_local_datetime(&local)
+      .single()
+      .ok_or_else(|| invalid("local date time is not representable"))
+  }
+}
+
+impl_option!(NaiveDateTime => crate::google_type::DateTime);
+impl_option!(DateTime<FixedOffset> => crate::google_type::DateTime);
+
+// Interval
+
+/// Time interval with optional bounds, `start` is inclusive and `end` is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DateTimeInterval {
+  pub start: Option<DateTime<Utc>>,
+  pub end: Option<DateTime<Utc>>,
+}
+
+impl DateTimeInterval {
+  pub fn contains(&self, value: &DateTime<Utc>) -> bool {
+    self.start.iter().all(|start| start <= value) && self.end.iter().all(|end| value < end)
+  }
+}
+
+impl S2ProtoPack<Interval> for DateTimeInterval {
+  fn pack(self) -> Result<Interval> {
+    check_interval(self.start.as_ref(), self.end.as_ref())?;
+    Ok(Interval {
+      start_time: self.start.pack()?,
+      end_time: self.end.pack()?,
+    })
+  }
+}
+
+impl S2ProtoUnpack<Interval> for DateTimeInterval {
+  fn unpack(value: Interval) -> Result<DateTimeInterval> {
+    let start = Option::<DateTime<Utc>>::unpack(value.start_time)?;
+    let end = Option::<DateTime<Utc>>::unpack(value.end_time)?;
+    check_interval(start.as_ref(), end.as_ref())?;
+    Ok(DateTimeInterval { start, end })
+  }
+}
+
+fn check_interval(start: Option<&DateTime<Utc>>, end: Option<&DateTime<Utc>>) -> Result<()> {
+  match (start, end) {
+    (Some(start), Some(end)) if start > end => Err(result::Error::InvalidInterval {
+      reason: "start_time is after end_time",
+    }),
+    _ => Ok(()),
+  }
+}
+
+impl S2ProtoPack<Interval> for Range<DateTime<Utc>> {
+  fn pack(self) -> Result<Interval> {
+    DateTimeInterval {
+      start: Some(self.start),
+      end: Some(self.end),
+    }
+    .pack()
+  }
+}
+
+impl S2ProtoUnpack<Interval> for Range<DateTime<Utc>> {
+  fn unpack(value: Interval) -> Result<Range<DateTime<Utc>>> {
+    match DateTimeInterval::unpack(value)? {
+      DateTimeInterval {
+        start: Some(start),
+        end: Some(end),
+      } => Ok(start..end),
+      DateTimeInterval { start: None, .. } => Err(result::Error::FieldValueNotPresent {
+        field_name: "start_time",
+      }),
+      DateTimeInterval { end: None, .. } => Err(result::Error::FieldValueNotPresent {
+        field_name: "end_time",
+      }),
+    }
+  }
+}
+
+impl S2ProtoPack<Interval> for RangeFrom<DateTime<Utc>> {
+  fn pack(self) -> Result<Interval> {
+    DateTimeInterval {
+      start: Some(self.start),
+      end: None,
+    }
+    .pack()
+  }
+}
+
+impl S2ProtoUnpack<Interval> for RangeFrom<DateTime<Utc>> {
+  fn unpack(value: Interval) -> Result<RangeFrom<DateTime<Utc>>> {
+    match DateTimeInterval::unpack(value)? {
+      DateTimeInterval {
+        start: Some(start),
+        end: None,
+      } => Ok(start..),
+      DateTimeInterval { start: None, .. } => Err(result::Error::FieldValueNotPresent {
+        field_name: "start_time",
+      }),
+      DateTimeInterval { end: Some(_), .. } => Err(result::Error::InvalidInterval {
+        reason: "end_time is set for an interval without end",
+      }),
+    }
+  }
+}
+
+impl S2ProtoPack<Interval> for RangeTo<DateTime<Utc>> {
+  fn pack(self) -> Result<Interval> {
+    DateTimeInterval {
+      start: None,
+      end: Some(self.end),
+    }
+    .pack()
+  }
+}
+
+impl S2ProtoUnpack<Interval> for RangeTo<DateTime<Utc>> {
+  fn unpack(value: Interval) -> Result<RangeTo<DateTime<Utc>>> {
+    match DateTimeInterval::unpack(value)? {
+      DateTimeInterval {
+        start: None,
+        end: Some(end),
+      } => Ok(..end),
+      DateTimeInterval { end: None, .. } => Err(result::Error::FieldValueNotPresent {
+        field_name: "end_time",
+      }),
+      DateTimeInterval { start: Some(_), .. } => Err(result::Error::InvalidInterval {
+        reason: "start_time is set for an interval without start",
+      }),
+    }
+  }
+}
+
+impl_option!(DateTimeInterval => Interval);
+impl_option!(Range<DateTime<Utc>> => Interval);
+impl_option!(RangeFrom<DateTime<Utc>> => Interval);
+impl_option!(RangeTo<DateTime<Utc>> => Interval);
+
+// Month and day of week
+//
+// The `*_UNSPECIFIED` values unpack to `None` for `Option` targets and fail otherwise. The
+// google.type value names are not prefixed with the enum name, so they are listed explicitly.
+
+macro_rules! impl_calendar_enum {
+  ($rust:ty => $proto:ty, $name:expr, { $($variant:ident => $proto_variant:ident $proto_name:literal),+ $(,)? }) => {
+    impl S2ProtoEnum for $rust {
+      type ProtoEnum = $proto;
+      const NAME: &'static str = $name;
+      const UNSPECIFIED: Option<i32> = Some(<$proto>::Unspecified as i32);
+
+      fn from_i32(v: i32) -> Option<Self> {
+        match v {
+          $(v if v == <$proto>::$proto_variant as i32 => Some(<$rust>::$variant),)+
+          _ => None,
+        }
+      }
+
+      fn pack(&self) -> $proto {
+        match *self {
+          $(<$rust>::$variant => <$proto>::$proto_variant,)+
+        }
+      }
+
+      fn get_variant_name(&self) -> &'static str {
+        match *self {
+          $(<$rust>::$variant => stringify!($variant),)+
+        }
+      }
+
+      fn from_variant_name(name: &str) -> Option<Self> {
+        match name {
+          $(stringify!($variant) => Some(<$rust>::$variant),)+
+          _ => None,
+        }
+      }
+
+      fn get_proto_variant_name(&self) -> Option<&'static str> {
+        match *self {
+          $(<$rust>::$variant => Some($proto_name),)+
+        }
+      }
+
+      fn from_proto_variant_name(name: &str) -> Option<Self> {
+        match name {
+          $($proto_name => Some(<$rust>::$variant),)+
+          _ => None,
+        }
+      }
+    }
+
+    impl S2ProtoEnumUnspecified for $rust {}
+
+    impl S2ProtoEnumVariants for $rust {
+      const ALL: &'static [Self] = &[$(<$rust>::$variant),+];
+    }
+  };
+}
+
+impl_calendar_enum!(Month => ProtoMonth, "Month", {
+  January => January "JANUARY",
+  February => February "FEBRUARY",
+  March => March "MARCH",
+  April => April "APRIL",
+  May => May "MAY",
+  June => June "JUNE",
+  July => July "JULY",
+  August => August "AUGUST",
+  September => September "SEPTEMBER",
+  October => October "OCTOBER",
+  November => November "NOVEMBER",
+  December => December "DECEMBER",
+});
+
+impl_calendar_enum!(Weekday => DayOfWeek, "Weekday", {
+  Mon => Monday "MONDAY",
+  Tue => Tuesday "TUESDAY",
+  Wed => Wednesday "WEDNESDAY",
+  Thu => Thursday "THURSDAY",
+  Fri => Friday "FRIDAY",
+  Sat => Saturday "SATURDAY",
+  Sun => Sunday "SUNDAY",
+});
@@ -0,0 +1,63 @@
+use chrono::{DateTime, LocalResult, TimeZone as _};
+use chrono_tz::Tz;
+
+use crate::convert_chrono::{pack_naive_date_time, unpack_naive_date_time};
+use crate::google_type::date_time::TimeOffset;
+use crate::google_type::TimeZone;
+use crate::result::{self, Result};
+use crate::{S2ProtoPack, S2ProtoUnpack};
+
+// Time zone
+
+impl S2ProtoPack<TimeZone> for Tz {
+  fn pack(self) -> Result<TimeZone> {
+    Ok(TimeZone {
+      id: self.name().to_string(),
+      version: String::new(),
+    })
+  }
+}
+
+impl S2ProtoUnpack<TimeZone> for Tz {
+  fn unpack(value: TimeZone) -> Result<Tz> {
+    value
+      .id
+      .parse()
+      .map_err(|_| result::Error::UnknownTimeZone { id: value.id })
+  }
+}
+
+impl_option!(Tz => TimeZone);
+
+// Date time
+
+impl S2ProtoPack<crate::google_type::DateTime> for DateTime<Tz> {
+  fn pack(self) -> Result<crate::google_type::DateTime> {
+    let time_zone = self.timezone().pack()?;
+    Ok(crate::google_type::DateTime {
+      time_offset: Some(TimeOffset::TimeZone(time_zone)),
+      ..pack_naive_date_time(self.naive_local())?
+    })
+  }
+}
+
+/// Ambiguous local times, e.g. when clocks are set back, resolve to the earliest instant
+impl S2ProtoUnpack<crate::google_type::DateTime> for DateTime<Tz> {
+  fn unpack(value: crate::google_type::DateTime) -> Result<DateTime<Tz>> {
+    let invalid = |reason| result::Error::InvalidTimeOffset { reason };
+    let tz = match value.time_offset {
+      Some(TimeOffset::TimeZone(ref time_zone)) => Tz::unpack(time_zone.clone())?,
+      Some(TimeOffset::UtcOffset(_)) => {
+        return Err(invalid("utc_offset can not be converted to a time zone"))
+      }
+      None => return Err(invalid("missing time_zone")),
+    };
+    let local = unpack_naive_date_time(&value)?;
+    match tz.from_local_datetime(&local) {
+      LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => Ok(dt),
+      LocalResult::None => Err(invalid("local date time does not exist in the time zone")),
+    }
+  }
+}
+
+impl_option!(DateTime<Tz> => crate::google_type::DateTime);
@@ -0,0 +1,147 @@
+use prost_types::Timestamp;
+use std::convert::TryFrom;
+use time::{Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+use crate::convert::{
+  invalid_date, invalid_time_of_day, normalize_timestamp, validate_duration, validate_full_date,
+  validate_time_of_day,
+};
+use crate::google_type::{Date, TimeOfDay};
+use crate::result::{self, Result};
+use crate::{S2ProtoPack, S2ProtoUnpack};
+
+// Timestamp
+
+impl S2ProtoPack<Timestamp> for OffsetDateTime {
+  fn pack(self) -> Result<Timestamp> {
+    let (seconds, nanos) = normalize_timestamp(self.unix_timestamp(), self.nanosecond() as i32)?;
+    Ok(Timestamp {
+      seconds,
+      nanos: nanos as i32,
+    })
+  }
+}
+
+impl S2ProtoUnpack<Timestamp> for OffsetDateTime {
+  fn unpack(Timestamp { seconds, nanos }: Timestamp) -> Result<OffsetDateTime> {
+    let (normalized_seconds, normalized_nanos) = normalize_timestamp(seconds, nanos)?;
+    OffsetDateTime::from_unix_timestamp_nanos(
+      i128::from(normalized_seconds) * 1_000_000_000 + i128::from(normalized_nanos),
+    )
+    .map_err(|_| result::Error::InvalidTimestamp {
+      seconds,
+      nanos,
+      reason: "not representable",
+    })
+  }
+}
+
+/// `PrimitiveDateTime` is assumed to be in UTC
+impl S2ProtoPack<Timestamp> for PrimitiveDateTime {
+  fn pack(self) -> Result<Timestamp> {
+    self.assume_utc().pack()
+  }
+}
+
+impl S2ProtoUnpack<Timestamp> for PrimitiveDateTime {
+  fn unpack(value: Timestamp) -> Result<PrimitiveDateTime> {
+    let dt = OffsetDateTime::unpack(value)?;
+    Ok(PrimitiveDateTime::new(dt.date(), dt.time()))
+  }
+}
+
+impl_option!(OffsetDateTime => Timestamp);
+impl_clone_ref!(OffsetDateTime => Timestamp);
+impl_option!(PrimitiveDateTime => Timestamp);
+impl_clone_ref!(PrimitiveDateTime => Timestamp);
+
+// Duration
+
+impl S2ProtoPack<prost_types::Duration> for time::Duration {
+  fn pack(self) -> Result<prost_types::Duration> {
+    let seconds = self.whole_seconds();
+    let nanos = self.subsec_nanoseconds();
+    validate_duration(seconds, nanos)?;
+    Ok(prost_types::Duration { seconds, nanos })
+  }
+}
+
+impl S2ProtoUnpack<prost_types::Duration> for time::Duration {
+  fn unpack(
+    prost_types::Duration { seconds, nanos }: prost_types::Duration,
+  ) -> Result<time::Duration> {
+    validate_duration(seconds, nanos)?;
+    Ok(time::Duration::new(seconds, nanos))
+  }
+}
+
+impl_option!(time::Duration => prost_types::Duration);
+impl_clone_ref!(time::Duration => prost_types::Duration);
+
+// Date
+
+impl S2ProtoPack<Date> for time::Date {
+  fn pack(self) -> Result<Date> {
+    let date = Date {
+      year: self.year(),
+      month: u8::from(self.month()) as i32,
+      day: i32::from(self.day()),
+    };
+    validate_full_date(&date)?;
+    Ok(date)
+  }
+}
+
+impl S2ProtoUnpack<Date> for time::Date {
+  fn unpack(value: Date) -> Result<time::Date> {
+    validate_full_date(&value)?;
+    let month =
+      Month::try_from(value.month as u8).map_err(|_| invalid_date(&value, "invalid month"))?;
+    time::Date::from_calendar_date(value.year, month, value.day as u8)
+      .map_err(|_| invalid_date(&value, "invalid calendar date"))
+  }
+}
+
+impl_option!(time::Date => Date);
+impl_clone_ref!(time::Date => Date);
+
+// Time of day
+
+impl S2ProtoPack<TimeOfDay> for Time {
+  fn pack(self) -> Result<TimeOfDay> {
+    Ok(TimeOfDay {
+      hours: i32::from(self.hour()),
+      minutes: i32::from(self.minute()),
+      seconds: i32::from(self.second()),
+      nanos: self.nanosecond() as i32,
+    })
+  }
+}
+
+impl S2ProtoUnpack<TimeOfDay> for Time {
+  fn unpack(value: TimeOfDay) -> Result<Time> {
+    validate_time_of_day(&value)?;
+    if value.hours == 24 {
+      return Err(invalid_time_of_day(
+        &value,
+        "24:00:00 can not be represented by time::Time",
+      ));
+    }
+    if value.seconds == 60 {
+      return Err(invalid_time_of_day(
+        &value,
+        "leap seconds can not be represented by time::Time",
+      ));
+    }
+    Time::from_hms_nano(
+      value.hours as u8,
+      value.minutes as u8,
+      value.seconds as u8,
+      value.nanos as u32,
+    )
+    .map_err(|_| invalid_time_of_day(&value, "not representable"))
+  }
+}
+
+impl_option!(Time => TimeOfDay);
+impl_clone_ref!(Time => TimeOfDay);
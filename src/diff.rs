@@ -0,0 +1,148 @@
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+use std::cmp::Eq;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Json, S2ProtoDiff, S2ProtoEnum};
+
+/// Joins a parent field path and a field name with `.`
+#[doc(hidden)]
+pub fn join_field_path(path: &str, name: &str) -> String {
+  if path.is_empty() {
+    name.to_string()
+  } else {
+    format!("{}.{}", path, name)
+  }
+}
+
+fn push_if_ne<T: PartialEq>(left: &T, right: &T, path: &str, paths: &mut Vec<String>) {
+  if left != right {
+    paths.push(path.to_string());
+  }
+}
+
+// Leaf values
+
+macro_rules! impl_leaf {
+  (
+    $($ty:ty),*
+  ) => {
+    $(
+      impl S2ProtoDiff for $ty {
+        fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+          push_if_ne(self, other, path, paths)
+        }
+      }
+    )*
+  }
+}
+
+impl_leaf! {
+  f32,
+  f64,
+  i64,
+  u64,
+  i32,
+  u32,
+  bool,
+  String,
+  JsonValue,
+  std::time::SystemTime,
+  std::time::Duration,
+  BigDecimal,
+  crate::google_type::Date,
+  crate::google_type::TimeOfDay,
+  crate::google_type::DateTime,
+  crate::google_type::TimeZone,
+  crate::google_type::Interval
+}
+
+#[cfg(feature = "chrono")]
+impl_leaf! {
+  chrono::DateTime<chrono::Utc>,
+  chrono::Duration,
+  chrono::NaiveDate,
+  chrono::NaiveTime,
+  chrono::NaiveDateTime,
+  chrono::DateTime<chrono::FixedOffset>,
+  crate::PartialDate,
+  crate::TimeOrEndOfDay,
+  crate::DateTimeInterval,
+  std::ops::Range<chrono::DateTime<chrono::Utc>>,
+  std::ops::RangeFrom<chrono::DateTime<chrono::Utc>>,
+  std::ops::RangeTo<chrono::DateTime<chrono::Utc>>
+}
+
+#[cfg(feature = "chrono-tz")]
+impl_leaf! {
+  chrono::DateTime<chrono_tz::Tz>,
+  chrono_tz::Tz
+}
+
+#[cfg(feature = "time")]
+impl_leaf! {
+  time::OffsetDateTime,
+  time::PrimitiveDateTime,
+  time::Duration,
+  time::Date,
+  time::Time
+}
+
+impl<T> S2ProtoDiff for Json<T>
+where
+  T: PartialEq,
+{
+  fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+    push_if_ne(&self.0, &other.0, path, paths)
+  }
+}
+
+// Enum
+
+impl<T> S2ProtoDiff for T
+where
+  T: S2ProtoEnum + PartialEq,
+{
+  fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+    push_if_ne(self, other, path, paths)
+  }
+}
+
+// Nested message
+
+impl<T> S2ProtoDiff for Option<T>
+where
+  T: S2ProtoDiff,
+{
+  fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+    match (self, other) {
+      (Some(left), Some(right)) => left.diff_paths(right, path, paths),
+      (None, None) => {}
+      _ => paths.push(path.to_string()),
+    }
+  }
+}
+
+// repeated value and map
+//
+// Field masks can not address individual elements, so any change replaces the whole field.
+
+impl<T> S2ProtoDiff for Vec<T>
+where
+  T: PartialEq,
+{
+  fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+    push_if_ne(self, other, path, paths)
+  }
+}
+
+impl<K, V> S2ProtoDiff for HashMap<K, V>
+where
+  K: Eq + Hash,
+  V: PartialEq,
+{
+  fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>) {
+    push_if_ne(self, other, path, paths)
+  }
+}
@@ -0,0 +1,202 @@
+//! Encodings of timestamps and durations in legacy `int64` and `string` fields
+//!
+//! The derive macros use them for fields with the `timestamp` and `duration` attributes:
+//!
+//! | Attribute              | Encoding            | Protobuf Type |
+//! | ---------------------- | ------------------- | ------------- |
+//! | `timestamp = "secs"`    | [`TimestampSecs`]    | `int64`       |
+//! | `timestamp = "millis"`  | [`TimestampMillis`]  | `int64`       |
+//! | `timestamp = "rfc3339"` | [`TimestampRfc3339`] | `string`      |
+//! | `duration = "secs"`     | [`DurationSecs`]     | `int64`       |
+//! | `duration = "millis"`   | [`DurationMillis`]   | `int64`       |
+//! | `duration = "string"`   | [`DurationString`]   | `string`      |
+//!
+//! Values must be within the ranges of `google.protobuf.Timestamp` and `Duration`. Integer
+//! encodings drop the precision below their unit, timestamps round down and durations toward zero.
+//!
+//! `Option` fields use [`Optional`] with the encoding, `0` or `""` means `None`.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use prost_types::Timestamp;
+use std::marker::PhantomData;
+
+use crate::result::{self, Result};
+use crate::{S2ProtoEncoding, S2ProtoPack, S2ProtoUnpack};
+
+/// `DateTime<Utc>` as `int64` seconds since the Unix epoch
+pub struct TimestampSecs;
+
+/// `DateTime<Utc>` as `int64` milliseconds since the Unix epoch
+pub struct TimestampMillis;
+
+/// `DateTime<Utc>` as an RFC 3339 `string`, e.g. `"2021-03-04T10:20:30.5Z"`
+pub struct TimestampRfc3339;
+
+/// `chrono::Duration` as `int64` seconds
+pub struct DurationSecs;
+
+/// `chrono::Duration` as `int64` milliseconds
+pub struct DurationMillis;
+
+/// `chrono::Duration` as a `string` in the proto JSON format, e.g. `"1.5s"`
+pub struct DurationString;
+
+/// `Option` of the values of encoding `E`, the default value of the proto type means `None`.
+/// The Unix epoch and zero durations are indistinguishable from `None` and unpack to it.
+pub struct Optional<E>(PhantomData<E>);
+
+// Timestamp
+
+impl S2ProtoEncoding<DateTime<Utc>, i64> for TimestampSecs {
+  fn pack(value: DateTime<Utc>) -> Result<i64> {
+    let Timestamp { seconds, .. } = value.pack()?;
+    Ok(seconds)
+  }
+
+  fn unpack(value: i64) -> Result<DateTime<Utc>> {
+    DateTime::unpack(Timestamp {
+      seconds: value,
+      nanos: 0,
+    })
+  }
+}
+
+impl S2ProtoEncoding<DateTime<Utc>, i64> for TimestampMillis {
+  fn pack(value: DateTime<Utc>) -> Result<i64> {
+    let Timestamp { seconds, nanos } = value.pack()?;
+    Ok(seconds * 1000 + i64::from(nanos / 1_000_000))
+  }
+
+  fn unpack(value: i64) -> Result<DateTime<Utc>> {
+    DateTime::unpack(Timestamp {
+      seconds: value.div_euclid(1000),
+      nanos: value.rem_euclid(1000) as i32 * 1_000_000,
+    })
+  }
+}
+
+impl S2ProtoEncoding<DateTime<Utc>, String> for TimestampRfc3339 {
+  fn pack(value: DateTime<Utc>) -> Result<String> {
+    let _: Timestamp = value.pack()?;
+    Ok(value.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+  }
+
+  fn unpack(value: String) -> Result<DateTime<Utc>> {
+    let value = DateTime::parse_from_rfc3339(&value)
+      .map_err(|err| result::Error::ParseTimestamp {
+        message: format!("'{}': {}", value, err),
+      })?
+      .with_timezone(&Utc);
+    let _: Timestamp = value.pack()?;
+    Ok(value)
+  }
+}
+
+// Duration
+
+impl S2ProtoEncoding<chrono::Duration, i64> for DurationSecs {
+  fn pack(value: chrono::Duration) -> Result<i64> {
+    let prost_types::Duration { seconds, .. } = value.pack()?;
+    Ok(seconds)
+  }
+
+  fn unpack(value: i64) -> Result<chrono::Duration> {
+    chrono::Duration::unpack(prost_types::Duration {
+      seconds: value,
+      nanos: 0,
+    })
+  }
+}
+
+impl S2ProtoEncoding<chrono::Duration, i64> for DurationMillis {
+  fn pack(value: chrono::Duration) -> Result<i64> {
+    let prost_types::Duration { seconds, nanos } = value.pack()?;
+    Ok(seconds * 1000 + i64::from(nanos / 1_000_000))
+  }
+
+  fn unpack(value: i64) -> Result<chrono::Duration> {
+    chrono::Duration::unpack(prost_types::Duration {
+      seconds: value / 1000,
+      nanos: (value % 1000) as i32 * 1_000_000,
+    })
+  }
+}
+
+impl S2ProtoEncoding<chrono::Duration, String> for DurationString {
+  fn pack(value: chrono::Duration) -> Result<String> {
+    let prost_types::Duration { seconds, nanos } = value.pack()?;
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    let (seconds, nanos) = (seconds.abs(), nanos.abs());
+    // Like the proto JSON format, use 0, 3, 6 or 9 fractional digits
+    Ok(if nanos == 0 {
+      format!("{}{}s", sign, seconds)
+    } else if nanos % 1_000_000 == 0 {
+      format!("{}{}.{:03}s", sign, seconds, nanos / 1_000_000)
+    } else if nanos % 1000 == 0 {
+      format!("{}{}.{:06}s", sign, seconds, nanos / 1000)
+    } else {
+      format!("{}{}.{:09}s", sign, seconds, nanos)
+    })
+  }
+
+  fn unpack(value: String) -> Result<chrono::Duration> {
+    let duration = parse_duration(&value).ok_or_else(|| result::Error::ParseDuration {
+      message: format!("'{}' is not a number of seconds like \"1.5s\"", value),
+    })?;
+    chrono::Duration::unpack(duration)
+  }
+}
+
+// Optional values
+
+impl<E, T, P> S2ProtoEncoding<Option<T>, P> for Optional<E>
+where
+  E: S2ProtoEncoding<T, P>,
+  P: Default + PartialEq,
+{
+  fn pack(value: Option<T>) -> Result<P> {
+    value.map_or_else(|| Ok(P::default()), E::pack)
+  }
+
+  fn unpack(value: P) -> Result<Option<T>> {
+    if value == P::default() {
+      Ok(None)
+    } else {
+      E::unpack(value).map(Some)
+    }
+  }
+}
+
+/// Parses `[-]<seconds>[.<fraction>]s` with up to 9 fractional digits
+fn parse_duration(value: &str) -> Option<prost_types::Duration> {
+  let value = value.strip_suffix('s')?;
+  let (negative, value) = match value.strip_prefix('-') {
+    Some(value) => (true, value),
+    None => (false, value),
+  };
+  let (seconds, fraction) = match value.find('.') {
+    Some(index) => (&value[..index], &value[index + 1..]),
+    None => (value, ""),
+  };
+  let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+  if seconds.is_empty() || !is_digits(seconds) || !is_digits(fraction) || fraction.len() > 9 {
+    return None;
+  }
+  if value.ends_with('.') {
+    return None;
+  }
+  let seconds: i64 = seconds.parse().ok()?;
+  let nanos = if fraction.is_empty() {
+    0
+  } else {
+    fraction.parse::<i32>().ok()? * 10i32.pow(9 - fraction.len() as u32)
+  };
+  Some(if negative {
+    prost_types::Duration {
+      seconds: -seconds,
+      nanos: -nanos,
+    }
+  } else {
+    prost_types::Duration { seconds, nanos }
+  })
+}
@@ -0,0 +1,122 @@
+//! Messages of the `google.type` package, for code which does not generate them itself
+//!
+//! See [googleapis](https://github.com/googleapis/googleapis/tree/master/google/type) for the
+//! definitions.
+
+/// `google.type.Date`, a whole or partial calendar date
+///
+/// A zero `year`, `month` or `day` means the value is not specified.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Date {
+  #[prost(int32, tag = "1")]
+  pub year: i32,
+  #[prost(int32, tag = "2")]
+  pub month: i32,
+  #[prost(int32, tag = "3")]
+  pub day: i32,
+}
+
+/// `google.type.TimeOfDay`, a time of day without date or time zone
+///
+/// `hours` may be 24 for the end of the day, and `seconds` may be 60 for leap seconds.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimeOfDay {
+  #[prost(int32, tag = "1")]
+  pub hours: i32,
+  #[prost(int32, tag = "2")]
+  pub minutes: i32,
+  #[prost(int32, tag = "3")]
+  pub seconds: i32,
+  #[prost(int32, tag = "4")]
+  pub nanos: i32,
+}
+
+/// `google.type.DateTime`, a civil time with an optional UTC offset or time zone
+///
+/// A zero `year` means the year is not specified.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DateTime {
+  #[prost(int32, tag = "1")]
+  pub year: i32,
+  #[prost(int32, tag = "2")]
+  pub month: i32,
+  #[prost(int32, tag = "3")]
+  pub day: i32,
+  #[prost(int32, tag = "4")]
+  pub hours: i32,
+  #[prost(int32, tag = "5")]
+  pub minutes: i32,
+  #[prost(int32, tag = "6")]
+  pub seconds: i32,
+  #[prost(int32, tag = "7")]
+  pub nanos: i32,
+  /// Local time when not set
+  #[prost(oneof = "date_time::TimeOffset", tags = "8, 9")]
+  pub time_offset: Option<date_time::TimeOffset>,
+}
+
+pub mod date_time {
+  #[derive(Clone, PartialEq, ::prost::Oneof)]
+  pub enum TimeOffset {
+    /// UTC offset, whole seconds between -18 and +18 hours
+    #[prost(message, tag = "8")]
+    UtcOffset(::prost_types::Duration),
+    #[prost(message, tag = "9")]
+    TimeZone(super::TimeZone),
+  }
+}
+
+/// `google.type.TimeZone`, an IANA time zone
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimeZone {
+  /// IANA time zone database id, e.g. "America/New_York"
+  #[prost(string, tag = "1")]
+  pub id: String,
+  /// Optional IANA time zone database version number, e.g. "2019a"
+  #[prost(string, tag = "2")]
+  pub version: String,
+}
+
+/// `google.type.Interval`, a time interval from `start_time` (inclusive) to `end_time` (exclusive)
+///
+/// A missing side is unbounded.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Interval {
+  #[prost(message, optional, tag = "1")]
+  pub start_time: Option<::prost_types::Timestamp>,
+  #[prost(message, optional, tag = "2")]
+  pub end_time: Option<::prost_types::Timestamp>,
+}
+
+/// `google.type.Month`, a month of the Gregorian calendar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Month {
+  Unspecified = 0,
+  January = 1,
+  February = 2,
+  March = 3,
+  April = 4,
+  May = 5,
+  June = 6,
+  July = 7,
+  August = 8,
+  September = 9,
+  October = 10,
+  November = 11,
+  December = 12,
+}
+
+/// `google.type.DayOfWeek`, a day of the week starting with Monday
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DayOfWeek {
+  Unspecified = 0,
+  Monday = 1,
+  Tuesday = 2,
+  Wednesday = 3,
+  Thursday = 4,
+  Friday = 5,
+  Saturday = 6,
+  Sunday = 7,
+}
@@ -1,13 +1,38 @@
+#[cfg(feature = "bitflags")]
+mod bitflags;
+#[macro_use]
 mod convert;
+#[cfg(feature = "chrono")]
+mod convert_chrono;
+#[cfg(feature = "chrono-tz")]
+mod convert_chrono_tz;
+#[cfg(feature = "time")]
+mod convert_time;
+mod diff;
+#[cfg(feature = "chrono")]
+pub mod encoding;
+pub mod google_type;
 pub mod result;
+pub mod schema;
 
 use crate::result::Error;
+use prost_types::FieldMask;
 
 #[allow(unused_imports)]
 #[macro_use]
 extern crate s2_grpc_utils_derive;
 
 pub use self::convert::Json;
+#[cfg(feature = "chrono")]
+pub use self::convert_chrono::{DateTimeInterval, PartialDate, TimeOrEndOfDay};
+#[doc(hidden)]
+pub use self::diff::join_field_path;
+
+/// Dependencies used by the generated code
+#[doc(hidden)]
+pub mod __private {
+  pub use serde;
+}
 pub use s2_grpc_utils_derive::*;
 
 pub trait S2ProtoPack<T>
@@ -24,16 +49,94 @@ where
   fn unpack(value: T) -> Result<Self, Error>;
 }
 
+/// Unpacks a value which borrows from the proto message instead of taking it by value
+pub trait S2ProtoUnpackRef<'a, T>
+where
+  Self: Sized,
+{
+  fn unpack_ref(value: &'a T) -> Result<Self, Error>;
+}
+
+/// Alternative encoding of `T` as the proto type `P`, used by fields with attributes like
+/// `#[s2_grpc(timestamp = "millis")]`
+pub trait S2ProtoEncoding<T, P> {
+  fn pack(value: T) -> Result<P, Error>;
+  fn unpack(value: P) -> Result<T, Error>;
+}
+
 pub trait S2ProtoEnum
 where
   Self: Sized,
 {
   type ProtoEnum;
   const NAME: &'static str;
+  /// Discriminant of the proto value which means `None`, it is rejected when unpacking `Self`
+  const UNSPECIFIED: Option<i32> = None;
 
   fn from_i32(v: i32) -> Option<Self>;
   fn pack(&self) -> Self::ProtoEnum;
   fn get_variant_name(&self) -> &'static str;
+
+  fn from_variant_name(_name: &str) -> Option<Self> {
+    None
+  }
+
+  /// Value name in the .proto file this value packs to, `None` for unrecognized values and
+  /// for impls which do not know the names
+  fn get_proto_variant_name(&self) -> Option<&'static str> {
+    None
+  }
+
+  fn from_proto_variant_name(_name: &str) -> Option<Self> {
+    None
+  }
+
+  /// Discriminant of this value, unlike `pack` it also keeps unrecognized discriminants
+  fn to_i32(&self) -> i32
+  where
+    Self::ProtoEnum: Into<i32>,
+  {
+    self.pack().into()
+  }
+}
+
+/// Lists the mapped variants of an enum, implemented by the derive
+pub trait S2ProtoEnumVariants: S2ProtoEnum + 'static {
+  /// All mapped variants in declaration order, without the variant holding unrecognized values
+  const ALL: &'static [Self];
+
+  /// All mapped variants with their discriminants
+  fn variants() -> Vec<(&'static Self, i32)>
+  where
+    Self::ProtoEnum: Into<i32>,
+  {
+    Self::ALL.iter().map(|v| (v, v.to_i32())).collect()
+  }
+}
+
+/// Enums with a proto value which means `None`, only these can pack and unpack `Option<Self>`
+/// as a plain enum field. Implementations must set `S2ProtoEnum::UNSPECIFIED`.
+pub trait S2ProtoEnumUnspecified: S2ProtoEnum {}
+
+pub trait S2ProtoDiff {
+  /// Appends the proto field paths which differ between `self` and `other` to `paths`,
+  /// `path` is the path of `self` in the root message
+  fn diff_paths(&self, other: &Self, path: &str, paths: &mut Vec<String>);
+
+  /// Computes a `FieldMask` which lists every proto field changed from `self` to `other`
+  fn diff_mask(&self, other: &Self) -> FieldMask {
+    let mut paths = vec![];
+    self.diff_paths(other, "", &mut paths);
+    FieldMask { paths }
+  }
+}
+
+pub trait S2ProtoSchema {
+  fn proto_schema() -> schema::Schema;
+}
+
+pub trait S2ProtoFieldType {
+  fn proto_field_type() -> schema::FieldType;
 }
 
 impl<T1, T2> S2ProtoPack<Option<T1>> for Option<T2>
@@ -61,3 +164,16 @@ where
     }
   }
 }
+
+impl<'a, T1, T2> S2ProtoUnpackRef<'a, Option<T1>> for Option<T2>
+where
+  T2: S2ProtoUnpackRef<'a, T1>,
+{
+  fn unpack_ref(value: &'a Option<T1>) -> Result<Self, Error> {
+    if let Some(value) = value.as_ref() {
+      Ok(Some(T2::unpack_ref(value)?))
+    } else {
+      Ok(None)
+    }
+  }
+}
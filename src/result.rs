@@ -11,6 +11,11 @@ pub enum Error {
   ValueNotPresent,
   #[snafu(display("Could not unpack field '{}' from null", field_name))]
   FieldValueNotPresent { field_name: &'static str },
+  #[snafu(display("Field '{}': {}", field_name, source))]
+  FieldValue {
+    field_name: &'static str,
+    source: Box<Error>,
+  },
   #[snafu(display("JSON value nested too deeply"))]
   JsonValueNestedTooDeeply,
   #[snafu(display("List element {}: {}", index, source))]
@@ -23,6 +28,64 @@ pub enum Error {
   },
   #[snafu(display("Parse duration error: {}", message))]
   ParseDuration { message: String },
+  #[snafu(display("Parse timestamp error: {}", message))]
+  ParseTimestamp { message: String },
+  #[snafu(display(
+    "Invalid timestamp: seconds = {}, nanos = {}: {}",
+    seconds,
+    nanos,
+    reason
+  ))]
+  InvalidTimestamp {
+    seconds: i64,
+    nanos: i32,
+    reason: &'static str,
+  },
+  #[snafu(display(
+    "Invalid duration: seconds = {}, nanos = {}: {}",
+    seconds,
+    nanos,
+    reason
+  ))]
+  InvalidDuration {
+    seconds: i64,
+    nanos: i32,
+    reason: &'static str,
+  },
+  #[snafu(display(
+    "Invalid date: year = {}, month = {}, day = {}: {}",
+    year,
+    month,
+    day,
+    reason
+  ))]
+  InvalidDate {
+    year: i32,
+    month: i32,
+    day: i32,
+    reason: &'static str,
+  },
+  #[snafu(display(
+    "Invalid time of day: hours = {}, minutes = {}, seconds = {}, nanos = {}: {}",
+    hours,
+    minutes,
+    seconds,
+    nanos,
+    reason
+  ))]
+  InvalidTimeOfDay {
+    hours: i32,
+    minutes: i32,
+    seconds: i32,
+    nanos: i32,
+    reason: &'static str,
+  },
+  #[snafu(display("Invalid date time offset: {}", reason))]
+  InvalidTimeOffset { reason: &'static str },
+  #[snafu(display("Unknown time zone: {}", id))]
+  UnknownTimeZone { id: String },
+  #[snafu(display("Invalid interval: {}", reason))]
+  InvalidInterval { reason: &'static str },
   #[snafu(display(
     "Enum discriminant is not found: enum type = {}, discriminant = {}",
     enum_name,
@@ -32,6 +95,31 @@ pub enum Error {
     enum_name: &'static str,
     discriminant: i32,
   },
+  #[snafu(display(
+    "Enum variant name is not found: enum type = {}, name = {}",
+    enum_name,
+    name
+  ))]
+  EnumVariantNameNotFound {
+    enum_name: &'static str,
+    name: String,
+  },
+  #[snafu(display(
+    "Enum has no unspecified value to pack None: enum type = {}",
+    enum_name
+  ))]
+  EnumUnspecifiedNotDefined { enum_name: &'static str },
+  #[snafu(display("Enum value has no proto variant name: enum type = {}", enum_name))]
+  EnumVariantNameNotPresent { enum_name: &'static str },
+  #[snafu(display(
+    "Flags have no proto enum value: flags type = {}, flags = {}",
+    flags_name,
+    flags
+  ))]
+  FlagsNotMapped {
+    flags_name: &'static str,
+    flags: String,
+  },
 }
 
 impl From<Error> for String {
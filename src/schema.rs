@@ -0,0 +1,336 @@
+//! Describes the proto messages and enums of Rust models, and renders them into `.proto` source
+
+use bigdecimal::BigDecimal;
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::convert::Json;
+use crate::S2ProtoFieldType;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+  /// Scalar value type, e.g. `int32`, `string`
+  Scalar(&'static str),
+  /// Fully qualified name of a well-known message or the name of a message in the same file
+  Message(String),
+  Enum(String),
+  Repeated(Box<FieldType>),
+  Map(Box<FieldType>, Box<FieldType>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+  pub name: String,
+  pub ty: FieldType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageSchema {
+  pub name: String,
+  pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumValue {
+  /// Value name in the .proto file, e.g. `STATUS_ACTIVE`
+  pub name: String,
+  pub number: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumSchema {
+  pub name: String,
+  pub values: Vec<EnumValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+  Message(MessageSchema),
+  Enum(EnumSchema),
+}
+
+impl Schema {
+  pub fn name(&self) -> &str {
+    match *self {
+      Schema::Message(ref m) => &m.name,
+      Schema::Enum(ref e) => &e.name,
+    }
+  }
+}
+
+/// Renders `schemas` into the source of a `proto3` file
+///
+/// Fields are numbered in declaration order. proto3 enums must start with a zero value, so the zero
+/// value is moved first, and enums without one get `<ENUM_NAME>_UNSPECIFIED = 0`.
+pub fn render_proto(package: &str, schemas: &[Schema]) -> String {
+  let mut imports = BTreeSet::new();
+  let mut body = String::new();
+
+  for schema in schemas {
+    body.push('\n');
+    match *schema {
+      Schema::Message(ref m) => {
+        writeln!(body, "message {} {{", m.name).unwrap();
+        for (i, field) in m.fields.iter().enumerate() {
+          collect_imports(&field.ty, &mut imports);
+          writeln!(
+            body,
+            "  {} {} = {};",
+            render_field_type(&field.ty),
+            field.name,
+            i + 1
+          )
+          .unwrap();
+        }
+        body.push_str("}\n");
+      }
+      Schema::Enum(ref e) => {
+        writeln!(body, "enum {} {{", e.name).unwrap();
+        match e.values.iter().find(|value| value.number == 0) {
+          Some(zero) => writeln!(body, "  {} = 0;", zero.name).unwrap(),
+          None => writeln!(
+            body,
+            "  {}_UNSPECIFIED = 0;",
+            to_screaming_snake_case(&e.name)
+          )
+          .unwrap(),
+        }
+        for value in e.values.iter().filter(|value| value.number != 0) {
+          writeln!(body, "  {} = {};", value.name, value.number).unwrap();
+        }
+        body.push_str("}\n");
+      }
+    }
+  }
+
+  let mut source = String::from("syntax = \"proto3\";\n");
+  if !package.is_empty() {
+    writeln!(source, "\npackage {};", package).unwrap();
+  }
+  if !imports.is_empty() {
+    source.push('\n');
+    for import in imports {
+      writeln!(source, "import \"{}\";", import).unwrap();
+    }
+  }
+  source.push_str(&body);
+  source
+}
+
+fn to_screaming_snake_case(name: &str) -> String {
+  let mut r = String::with_capacity(name.len() + 4);
+  let mut prev_lower = false;
+  for c in name.chars() {
+    if c.is_uppercase() && prev_lower {
+      r.push('_');
+    }
+    prev_lower = c.is_lowercase() || c.is_ascii_digit();
+    r.extend(c.to_uppercase());
+  }
+  r
+}
+
+fn render_field_type(ty: &FieldType) -> String {
+  match *ty {
+    FieldType::Scalar(name) => name.to_string(),
+    FieldType::Message(ref name) | FieldType::Enum(ref name) => name.clone(),
+    FieldType::Repeated(ref ty) => format!("repeated {}", render_field_type(ty)),
+    FieldType::Map(ref k, ref v) => {
+      format!("map<{}, {}>", render_field_type(k), render_field_type(v))
+    }
+  }
+}
+
+fn collect_imports(ty: &FieldType, imports: &mut BTreeSet<String>) {
+  match *ty {
+    FieldType::Scalar(_) => {}
+    FieldType::Message(ref name) | FieldType::Enum(ref name) => {
+      if let Some(path) = well_known_import(name) {
+        imports.insert(path);
+      }
+    }
+    FieldType::Repeated(ref ty) => collect_imports(ty, imports),
+    FieldType::Map(ref k, ref v) => {
+      collect_imports(k, imports);
+      collect_imports(v, imports);
+    }
+  }
+}
+
+fn well_known_import(name: &str) -> Option<String> {
+  if let Some(name) = name.strip_prefix("google.protobuf.") {
+    let file = match name {
+      "Value" | "Struct" | "ListValue" => "struct",
+      "FieldMask" => "field_mask",
+      name if name.ends_with("Value") => "wrappers",
+      name => return Some(format!("google/protobuf/{}.proto", name.to_lowercase())),
+    };
+    Some(format!("google/protobuf/{}.proto", file))
+  } else {
+    name.strip_prefix("google.type.").map(|name| {
+      let file = match name {
+        "TimeZone" => "datetime".to_string(),
+        name => name.to_lowercase(),
+      };
+      format!("google/type/{}.proto", file)
+    })
+  }
+}
+
+// Scalars
+
+macro_rules! impl_scalar {
+  (
+    $($ty:ty => $name:expr),*
+  ) => {
+    $(
+      impl S2ProtoFieldType for $ty {
+        fn proto_field_type() -> FieldType {
+          FieldType::Scalar($name)
+        }
+      }
+    )*
+  }
+}
+
+impl_scalar! {
+  f32 => "float",
+  f64 => "double",
+  i64 => "int64",
+  u64 => "uint64",
+  i32 => "int32",
+  u32 => "uint32",
+  bool => "bool",
+  String => "string",
+  BigDecimal => "string"
+}
+
+// Well-known types
+
+macro_rules! impl_well_known {
+  (
+    $($ty:ty => $name:expr),*
+  ) => {
+    $(
+      impl S2ProtoFieldType for $ty {
+        fn proto_field_type() -> FieldType {
+          FieldType::Message($name.to_string())
+        }
+      }
+    )*
+  }
+}
+
+impl_well_known! {
+  JsonValue => "google.protobuf.Value",
+  std::time::SystemTime => "google.protobuf.Timestamp",
+  std::time::Duration => "google.protobuf.Duration",
+  crate::google_type::Date => "google.type.Date",
+  crate::google_type::TimeOfDay => "google.type.TimeOfDay",
+  crate::google_type::DateTime => "google.type.DateTime",
+  crate::google_type::TimeZone => "google.type.TimeZone",
+  crate::google_type::Interval => "google.type.Interval"
+}
+
+#[cfg(feature = "chrono")]
+impl_well_known! {
+  chrono::DateTime<chrono::Utc> => "google.protobuf.Timestamp",
+  chrono::Duration => "google.protobuf.Duration",
+  chrono::NaiveDate => "google.type.Date",
+  chrono::NaiveTime => "google.type.TimeOfDay",
+  chrono::NaiveDateTime => "google.type.DateTime",
+  chrono::DateTime<chrono::FixedOffset> => "google.type.DateTime",
+  crate::PartialDate => "google.type.Date",
+  crate::TimeOrEndOfDay => "google.type.TimeOfDay",
+  crate::DateTimeInterval => "google.type.Interval",
+  std::ops::Range<chrono::DateTime<chrono::Utc>> => "google.type.Interval",
+  std::ops::RangeFrom<chrono::DateTime<chrono::Utc>> => "google.type.Interval",
+  std::ops::RangeTo<chrono::DateTime<chrono::Utc>> => "google.type.Interval"
+}
+
+#[cfg(feature = "chrono")]
+impl S2ProtoFieldType for chrono::Month {
+  fn proto_field_type() -> FieldType {
+    FieldType::Enum("google.type.Month".to_string())
+  }
+}
+
+#[cfg(feature = "chrono")]
+impl S2ProtoFieldType for chrono::Weekday {
+  fn proto_field_type() -> FieldType {
+    FieldType::Enum("google.type.DayOfWeek".to_string())
+  }
+}
+
+#[cfg(feature = "chrono-tz")]
+impl_well_known! {
+  chrono::DateTime<chrono_tz::Tz> => "google.type.DateTime",
+  chrono_tz::Tz => "google.type.TimeZone"
+}
+
+#[cfg(feature = "time")]
+impl_well_known! {
+  time::OffsetDateTime => "google.protobuf.Timestamp",
+  time::PrimitiveDateTime => "google.protobuf.Timestamp",
+  time::Duration => "google.protobuf.Duration",
+  time::Date => "google.type.Date",
+  time::Time => "google.type.TimeOfDay"
+}
+
+impl<T> S2ProtoFieldType for Json<T> {
+  fn proto_field_type() -> FieldType {
+    FieldType::Message("google.protobuf.Value".to_string())
+  }
+}
+
+// Optional values use wrappers for scalars
+
+impl<T> S2ProtoFieldType for Option<T>
+where
+  T: S2ProtoFieldType,
+{
+  fn proto_field_type() -> FieldType {
+    match T::proto_field_type() {
+      FieldType::Scalar(name) => {
+        let wrapper = match name {
+          "float" => "FloatValue",
+          "double" => "DoubleValue",
+          "int64" => "Int64Value",
+          "uint64" => "UInt64Value",
+          "int32" => "Int32Value",
+          "uint32" => "UInt32Value",
+          "bool" => "BoolValue",
+          "string" => "StringValue",
+          _ => return FieldType::Scalar(name),
+        };
+        FieldType::Message(format!("google.protobuf.{}", wrapper))
+      }
+      ty => ty,
+    }
+  }
+}
+
+// repeated value and map
+
+impl<T> S2ProtoFieldType for Vec<T>
+where
+  T: S2ProtoFieldType,
+{
+  fn proto_field_type() -> FieldType {
+    FieldType::Repeated(Box::new(T::proto_field_type()))
+  }
+}
+
+impl<K, V> S2ProtoFieldType for HashMap<K, V>
+where
+  K: S2ProtoFieldType,
+  V: S2ProtoFieldType,
+{
+  fn proto_field_type() -> FieldType {
+    FieldType::Map(
+      Box::new(K::proto_field_type()),
+      Box::new(V::proto_field_type()),
+    )
+  }
+}
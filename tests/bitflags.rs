@@ -0,0 +1,77 @@
+#![cfg(feature = "bitflags")]
+
+use bitflags::bitflags;
+use s2_grpc_utils::schema::FieldType;
+use s2_grpc_utils::{S2ProtoFieldType, S2ProtoPack, S2ProtoUnpack};
+
+#[allow(dead_code)]
+enum Permission {
+  Unspecified = 0,
+  Read = 1,
+  Write = 2,
+  Admin = 3,
+}
+
+bitflags! {
+  struct Permissions: u32 {
+    const READ = 0b001;
+    const WRITE = 0b010;
+    const ADMIN = 0b100;
+  }
+}
+
+bitflags! {
+  struct Modes: u32 {
+    const READ = 0b001;
+    const SETUID = 0b010;
+  }
+}
+
+s2_grpc_utils::s2_proto_bitflags!(Permissions => Permission {
+  READ => Read,
+  WRITE => Write,
+  ADMIN => Admin,
+});
+
+s2_grpc_utils::s2_proto_bitflags!(Modes => Permission {
+  READ => Read,
+});
+
+#[test]
+fn bitflags_pack() {
+  let values: Vec<i32> = (Permissions::READ | Permissions::ADMIN).pack().unwrap();
+  assert_eq!(values, vec![1, 3]);
+  let values: Vec<i32> = Permissions::empty().pack().unwrap();
+  assert!(values.is_empty());
+}
+
+#[test]
+fn bitflags_pack_unmapped() {
+  let values: Vec<i32> = Modes::READ.pack().unwrap();
+  assert_eq!(values, vec![1]);
+  let res: Result<Vec<i32>, _> = (Modes::READ | Modes::SETUID).pack();
+  assert_eq!(
+    format!("{}", res.err().unwrap()),
+    "Flags have no proto enum value: flags type = Modes, flags = SETUID"
+  );
+}
+
+#[test]
+fn bitflags_unpack() {
+  assert_eq!(
+    Permissions::unpack(vec![2, 1, 2]).unwrap(),
+    Permissions::READ | Permissions::WRITE
+  );
+  assert_eq!(
+    format!("{}", Permissions::unpack(vec![1, 0]).err().unwrap()),
+    "Enum discriminant is not found: enum type = Permission, discriminant = 0"
+  );
+}
+
+#[test]
+fn bitflags_field_type() {
+  assert_eq!(
+    Permissions::proto_field_type(),
+    FieldType::Repeated(Box::new(FieldType::Enum("Permission".to_string())))
+  );
+}
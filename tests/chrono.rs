@@ -0,0 +1,578 @@
+#![cfg(feature = "chrono")]
+
+use chrono::{DateTime, TimeZone, Utc};
+use prost_types::Timestamp;
+use s2_grpc_utils::{S2ProtoEnumVariants, S2ProtoPack, S2ProtoUnpack};
+
+#[test]
+fn timestamp_range() {
+  let min = Timestamp {
+    seconds: -62_135_596_800,
+    nanos: 0,
+  };
+  let max = Timestamp {
+    seconds: 253_402_300_799,
+    nanos: 999_999_999,
+  };
+  assert_eq!(
+    DateTime::<Utc>::unpack(min.clone()).unwrap(),
+    Utc.ymd(1, 1, 1).and_hms(0, 0, 0)
+  );
+  assert_eq!(
+    DateTime::<Utc>::unpack(max.clone()).unwrap(),
+    Utc.ymd(9999, 12, 31).and_hms_nano(23, 59, 59, 999_999_999)
+  );
+  assert_eq!(
+    format!(
+      "{}",
+      DateTime::<Utc>::unpack(Timestamp {
+        seconds: 253_402_300_799,
+        nanos: 1_000_000_000,
+      })
+      .err()
+      .unwrap()
+    ),
+    "Invalid timestamp: seconds = 253402300799, nanos = 1000000000: out of range 0001-01-01T00:00:00Z to 9999-12-31T23:59:59.999999999Z"
+  );
+  assert!(DateTime::<Utc>::unpack(Timestamp {
+    seconds: i64::MAX,
+    nanos: i32::MAX,
+  })
+  .is_err());
+  assert!(DateTime::<Utc>::unpack(Timestamp {
+    seconds: i64::MIN,
+    nanos: 0,
+  })
+  .is_err());
+
+  let packed: Result<Timestamp, _> = Utc.ymd(10000, 1, 1).and_hms(0, 0, 0).pack();
+  assert!(packed.is_err());
+}
+
+#[test]
+fn timestamp_normalize_nanos() {
+  assert_eq!(
+    DateTime::<Utc>::unpack(Timestamp {
+      seconds: 10,
+      nanos: -1,
+    })
+    .unwrap(),
+    Utc.timestamp(9, 999_999_999)
+  );
+  assert_eq!(
+    DateTime::<Utc>::unpack(Timestamp {
+      seconds: 10,
+      nanos: -1_500_000_000,
+    })
+    .unwrap(),
+    Utc.timestamp(8, 500_000_000)
+  );
+
+  let leap_second = Utc
+    .ymd(2016, 12, 31)
+    .and_hms_nano(23, 59, 59, 1_500_000_000);
+  let packed: Timestamp = leap_second.pack().unwrap();
+  assert_eq!(
+    packed,
+    Timestamp {
+      seconds: Utc.ymd(2017, 1, 1).and_hms(0, 0, 0).timestamp(),
+      nanos: 500_000_000,
+    }
+  );
+}
+
+#[test]
+fn duration_signed() {
+  let cases = vec![
+    (chrono::Duration::milliseconds(1500), 1, 500_000_000),
+    (chrono::Duration::milliseconds(-1500), -1, -500_000_000),
+    (chrono::Duration::nanoseconds(-1), 0, -1),
+    (
+      chrono::Duration::seconds(-315_576_000_000),
+      -315_576_000_000,
+      0,
+    ),
+  ];
+  for (duration, seconds, nanos) in cases {
+    let packed: prost_types::Duration = duration.pack().unwrap();
+    assert_eq!(packed, prost_types::Duration { seconds, nanos });
+    assert_eq!(chrono::Duration::unpack(packed).unwrap(), duration);
+  }
+}
+
+#[test]
+fn duration_invalid() {
+  let message = |seconds, nanos| {
+    format!(
+      "{}",
+      chrono::Duration::unpack(prost_types::Duration { seconds, nanos })
+        .err()
+        .unwrap()
+    )
+  };
+  assert_eq!(
+    message(315_576_000_001, 0),
+    "Invalid duration: seconds = 315576000001, nanos = 0: seconds out of range -315576000000 to 315576000000"
+  );
+  assert_eq!(
+    message(0, 1_000_000_000),
+    "Invalid duration: seconds = 0, nanos = 1000000000: nanos out of range -999999999 to 999999999"
+  );
+  assert_eq!(
+    message(1, -1),
+    "Invalid duration: seconds = 1, nanos = -1: seconds and nanos have different signs"
+  );
+
+  let packed: Result<prost_types::Duration, _> = chrono::Duration::days(3_660_000).pack();
+  assert!(packed.is_err());
+}
+
+#[test]
+fn naive_date() {
+  use chrono::NaiveDate;
+  use s2_grpc_utils::google_type::Date;
+
+  let date = NaiveDate::from_ymd(2020, 2, 29);
+  let packed: Date = date.pack().unwrap();
+  assert_eq!(
+    packed,
+    Date {
+      year: 2020,
+      month: 2,
+      day: 29,
+    }
+  );
+  assert_eq!(NaiveDate::unpack(packed).unwrap(), date);
+
+  let message = |year, month, day| {
+    format!(
+      "{}",
+      NaiveDate::unpack(Date { year, month, day }).err().unwrap()
+    )
+  };
+  assert_eq!(
+    message(2021, 2, 29),
+    "Invalid date: year = 2021, month = 2, day = 29: invalid calendar date"
+  );
+  assert_eq!(
+    message(2021, 2, 0),
+    "Invalid date: year = 2021, month = 2, day = 0: partial dates are not supported"
+  );
+  assert_eq!(
+    message(2021, 13, 1),
+    "Invalid date: year = 2021, month = 13, day = 1: month out of range 1 to 12"
+  );
+
+  let packed: Result<Date, _> = NaiveDate::from_ymd(10000, 1, 1).pack();
+  assert!(packed.is_err());
+}
+
+#[test]
+fn partial_date() {
+  use chrono::NaiveDate;
+  use s2_grpc_utils::google_type::Date;
+  use s2_grpc_utils::PartialDate;
+
+  let cases = vec![
+    (
+      PartialDate::Full(NaiveDate::from_ymd(2021, 3, 4)),
+      (2021, 3, 4),
+    ),
+    (
+      PartialDate::YearMonth {
+        year: 2021,
+        month: 3,
+      },
+      (2021, 3, 0),
+    ),
+    (PartialDate::Year(2021), (2021, 0, 0)),
+    (PartialDate::MonthDay { month: 2, day: 29 }, (0, 2, 29)),
+  ];
+  for (partial, (year, month, day)) in cases {
+    let packed: Date = partial.pack().unwrap();
+    assert_eq!(packed, Date { year, month, day });
+    assert_eq!(PartialDate::unpack(packed).unwrap(), partial);
+  }
+
+  let message = |year, month, day| {
+    format!(
+      "{}",
+      PartialDate::unpack(Date { year, month, day })
+        .err()
+        .unwrap()
+    )
+  };
+  assert_eq!(
+    message(0, 0, 0),
+    "Invalid date: year = 0, month = 0, day = 0: a date needs at least a year or a month and a day"
+  );
+  assert_eq!(
+    message(2021, 0, 5),
+    "Invalid date: year = 2021, month = 0, day = 5: day without month"
+  );
+  assert_eq!(
+    message(0, 4, 31),
+    "Invalid date: year = 0, month = 4, day = 31: invalid calendar date"
+  );
+  let packed: Result<Date, _> = PartialDate::MonthDay { month: 2, day: 30 }.pack();
+  assert!(packed.is_err());
+}
+
+#[test]
+fn naive_time() {
+  use chrono::NaiveTime;
+  use s2_grpc_utils::google_type::TimeOfDay;
+
+  let cases = vec![
+    (NaiveTime::from_hms_nano(9, 30, 15, 5), (9, 30, 15, 5)),
+    (NaiveTime::from_hms(0, 0, 0), (0, 0, 0, 0)),
+    (
+      NaiveTime::from_hms_nano(23, 59, 59, 1_000_000_001),
+      (23, 59, 60, 1),
+    ),
+  ];
+  for (time, (hours, minutes, seconds, nanos)) in cases {
+    let packed: TimeOfDay = time.pack().unwrap();
+    assert_eq!(
+      packed,
+      TimeOfDay {
+        hours,
+        minutes,
+        seconds,
+        nanos,
+      }
+    );
+    assert_eq!(NaiveTime::unpack(packed).unwrap(), time);
+  }
+
+  let message = |hours, minutes, seconds, nanos| {
+    format!(
+      "{}",
+      NaiveTime::unpack(TimeOfDay {
+        hours,
+        minutes,
+        seconds,
+        nanos,
+      })
+      .err()
+      .unwrap()
+    )
+  };
+  assert_eq!(
+    message(24, 0, 0, 0),
+    "Invalid time of day: hours = 24, minutes = 0, seconds = 0, nanos = 0: 24:00:00 can not be represented by NaiveTime"
+  );
+  assert_eq!(
+    message(12, 0, 61, 0),
+    "Invalid time of day: hours = 12, minutes = 0, seconds = 61, nanos = 0: seconds out of range 0 to 60"
+  );
+  assert_eq!(
+    message(12, 0, 0, -1),
+    "Invalid time of day: hours = 12, minutes = 0, seconds = 0, nanos = -1: nanos out of range 0 to 999999999"
+  );
+}
+
+#[test]
+fn time_or_end_of_day() {
+  use chrono::NaiveTime;
+  use s2_grpc_utils::google_type::TimeOfDay;
+  use s2_grpc_utils::TimeOrEndOfDay;
+
+  let end_of_day = TimeOfDay {
+    hours: 24,
+    minutes: 0,
+    seconds: 0,
+    nanos: 0,
+  };
+  assert_eq!(
+    TimeOrEndOfDay::unpack(end_of_day.clone()).unwrap(),
+    TimeOrEndOfDay::EndOfDay
+  );
+  let packed: TimeOfDay = TimeOrEndOfDay::EndOfDay.pack().unwrap();
+  assert_eq!(packed, end_of_day);
+
+  let time = TimeOrEndOfDay::Time(NaiveTime::from_hms(23, 59, 59));
+  let packed: TimeOfDay = time.pack().unwrap();
+  assert_eq!(
+    packed,
+    TimeOfDay {
+      hours: 23,
+      minutes: 59,
+      seconds: 59,
+      nanos: 0,
+    }
+  );
+  assert_eq!(TimeOrEndOfDay::unpack(packed).unwrap(), time);
+  assert!(time < TimeOrEndOfDay::EndOfDay);
+
+  assert_eq!(
+    format!(
+      "{}",
+      TimeOrEndOfDay::unpack(TimeOfDay {
+        hours: 24,
+        minutes: 0,
+        seconds: 1,
+        nanos: 0,
+      })
+      .err()
+      .unwrap()
+    ),
+    "Invalid time of day: hours = 24, minutes = 0, seconds = 1, nanos = 0: hours can only be 24 for 24:00:00"
+  );
+}
+
+#[test]
+fn date_time_offset() {
+  use chrono::{FixedOffset, NaiveDate, NaiveDateTime};
+  use s2_grpc_utils::google_type::{date_time::TimeOffset, DateTime as ProtoDateTime, TimeZone};
+
+  let local = NaiveDate::from_ymd(2021, 3, 4).and_hms_nano(10, 20, 30, 40);
+  let message = ProtoDateTime {
+    year: 2021,
+    month: 3,
+    day: 4,
+    hours: 10,
+    minutes: 20,
+    seconds: 30,
+    nanos: 40,
+    time_offset: None,
+  };
+
+  let packed: ProtoDateTime = local.pack().unwrap();
+  assert_eq!(packed, message);
+  assert_eq!(NaiveDateTime::unpack(packed).unwrap(), local);
+
+  let zoned = FixedOffset::west_opt(5 * 3600)
+    .unwrap()
+    .from_local_datetime(&local)
+    .unwrap();
+  let with_offset = ProtoDateTime {
+    time_offset: Some(TimeOffset::UtcOffset(prost_types::Duration {
+      seconds: -5 * 3600,
+      nanos: 0,
+    })),
+    ..message.clone()
+  };
+  let packed: ProtoDateTime = zoned.pack().unwrap();
+  assert_eq!(packed, with_offset);
+  assert_eq!(DateTime::<FixedOffset>::unpack(packed).unwrap(), zoned);
+
+  let error =
+    |result: Result<_, s2_grpc_utils::result::Error>| format!("{}", result.err().unwrap());
+  assert_eq!(
+    error(NaiveDateTime::unpack(with_offset).map(|_| ())),
+    "Invalid date time offset: local date time can not have a UTC offset or time zone"
+  );
+  assert_eq!(
+    error(DateTime::<FixedOffset>::unpack(message.clone()).map(|_| ())),
+    "Invalid date time offset: missing utc_offset"
+  );
+  assert_eq!(
+    error(
+      DateTime::<FixedOffset>::unpack(ProtoDateTime {
+        time_offset: Some(TimeOffset::TimeZone(TimeZone {
+          id: "Europe/Paris".to_string(),
+          version: String::new(),
+        })),
+        ..message.clone()
+      })
+      .map(|_| ())
+    ),
+    "Invalid date time offset: time zone can not be converted to a fixed offset"
+  );
+  assert_eq!(
+    error(
+      DateTime::<FixedOffset>::unpack(ProtoDateTime {
+        time_offset: Some(TimeOffset::UtcOffset(prost_types::Duration {
+          seconds: 19 * 3600,
+          nanos: 0,
+        })),
+        ..message.clone()
+      })
+      .map(|_| ())
+    ),
+    "Invalid date time offset: utc_offset out of range -18 to +18 hours"
+  );
+  assert_eq!(
+    error(
+      DateTime::<FixedOffset>::unpack(ProtoDateTime {
+        time_offset: Some(TimeOffset::UtcOffset(prost_types::Duration {
+          seconds: 3600,
+          nanos: 500_000_000,
+        })),
+        ..message.clone()
+      })
+      .map(|_| ())
+    ),
+    "Invalid date time offset: utc_offset must be whole seconds"
+  );
+
+  let max_offset = FixedOffset::east_opt(18 * 3600).unwrap();
+  let packed: ProtoDateTime = max_offset
+    .from_local_datetime(&local)
+    .unwrap()
+    .pack()
+    .unwrap();
+  assert_eq!(
+    DateTime::<FixedOffset>::unpack(packed).unwrap().offset(),
+    &max_offset
+  );
+  let packed: Result<ProtoDateTime, _> = FixedOffset::west_opt(19 * 3600)
+    .unwrap()
+    .from_local_datetime(&local)
+    .unwrap()
+    .pack();
+  assert_eq!(
+    error(packed.map(|_| ())),
+    "Invalid date time offset: utc_offset out of range -18 to +18 hours"
+  );
+  assert_eq!(
+    error(NaiveDateTime::unpack(ProtoDateTime { year: 0, ..message }).map(|_| ())),
+    "Invalid date: year = 0, month = 3, day = 4: partial dates are not supported"
+  );
+}
+
+#[test]
+fn interval() {
+  use s2_grpc_utils::google_type::Interval;
+  use s2_grpc_utils::DateTimeInterval;
+  use std::ops::{Range, RangeFrom, RangeTo};
+
+  let start = Utc.ymd(2021, 3, 4).and_hms(10, 0, 0);
+  let end = Utc.ymd(2021, 3, 4).and_hms(11, 0, 0);
+  let error =
+    |result: Result<_, s2_grpc_utils::result::Error>| format!("{}", result.err().unwrap());
+  let start_time: Option<Timestamp> = Some(start).pack().unwrap();
+  let end_time: Option<Timestamp> = Some(end).pack().unwrap();
+  let message = Interval {
+    start_time: start_time.clone(),
+    end_time: end_time.clone(),
+  };
+
+  let packed: Interval = (start..end).pack().unwrap();
+  assert_eq!(packed, message);
+  assert_eq!(Range::unpack(packed).unwrap(), start..end);
+
+  let packed: Interval = (start..).pack().unwrap();
+  assert_eq!(packed.end_time, None);
+  assert_eq!(RangeFrom::unpack(packed).unwrap(), start..);
+
+  let packed: Interval = (..end).pack().unwrap();
+  assert_eq!(packed.start_time, None);
+  assert_eq!(RangeTo::unpack(packed).unwrap(), ..end);
+
+  let unbounded = DateTimeInterval::unpack(Interval::default()).unwrap();
+  assert_eq!(unbounded, DateTimeInterval::default());
+  assert!(unbounded.contains(&start));
+  let interval = DateTimeInterval::unpack(message.clone()).unwrap();
+  assert!(interval.contains(&start));
+  assert!(!interval.contains(&end));
+
+  assert_eq!(
+    error(
+      Range::<DateTime<Utc>>::unpack(Interval {
+        start_time: None,
+        ..message.clone()
+      })
+      .map(|_| ())
+    ),
+    "Could not unpack field 'start_time' from null"
+  );
+  assert_eq!(
+    error(RangeFrom::<DateTime<Utc>>::unpack(message.clone()).map(|_| ())),
+    "Invalid interval: end_time is set for an interval without end"
+  );
+  assert_eq!(
+    error(
+      DateTimeInterval::unpack(Interval {
+        start_time: end_time,
+        end_time: start_time,
+      })
+      .map(|_| ())
+    ),
+    "Invalid interval: start_time is after end_time"
+  );
+  assert_eq!(
+    error((end..start).pack().map(|_: Interval| ())),
+    "Invalid interval: start_time is after end_time"
+  );
+}
+
+#[test]
+fn month_and_weekday() {
+  use chrono::{Month, Weekday};
+  use s2_grpc_utils::google_type::{DayOfWeek, Month as ProtoMonth};
+  use s2_grpc_utils::S2ProtoEnum;
+
+  assert_eq!(Month::January.pack().unwrap(), ProtoMonth::January as i32);
+  assert_eq!(Month::unpack(12).unwrap(), Month::December);
+  assert_eq!(Weekday::Mon.pack().unwrap(), DayOfWeek::Monday as i32);
+  assert_eq!(Weekday::unpack(7).unwrap(), Weekday::Sun);
+  assert_eq!(Weekday::ALL.len(), 7);
+  assert_eq!(Weekday::Wed.get_proto_variant_name(), Some("WEDNESDAY"));
+
+  assert_eq!(Option::<Month>::unpack(0).unwrap(), None);
+  assert_eq!(Option::<Weekday>::unpack(5).unwrap(), Some(Weekday::Fri));
+  let packed: i32 = Option::<Weekday>::None.pack().unwrap();
+  assert_eq!(packed, 0);
+
+  let error =
+    |result: Result<_, s2_grpc_utils::result::Error>| format!("{}", result.err().unwrap());
+  assert_eq!(
+    error(Month::unpack(0).map(|_| ())),
+    "Could not unpack a non-optional value from null"
+  );
+  assert_eq!(
+    error(Weekday::unpack(8).map(|_| ())),
+    "Enum discriminant is not found: enum type = Weekday, discriminant = 8"
+  );
+}
+
+#[test]
+fn encodings() {
+  use chrono::Duration;
+  use s2_grpc_utils::encoding::{DurationSecs, DurationString, TimestampMillis, TimestampSecs};
+  use s2_grpc_utils::S2ProtoEncoding;
+
+  let pack = |d: Duration| DurationString::pack(d).unwrap();
+  assert_eq!(pack(Duration::zero()), "0s");
+  assert_eq!(pack(Duration::seconds(3)), "3s");
+  assert_eq!(pack(Duration::milliseconds(-500)), "-0.500s");
+  assert_eq!(pack(Duration::microseconds(1_000_001)), "1.000001s");
+  assert_eq!(pack(Duration::nanoseconds(-1_000_000_001)), "-1.000000001s");
+
+  let unpack = |s: &str| DurationString::unpack(s.to_string()).ok();
+  assert_eq!(unpack("-0.5s"), Some(Duration::milliseconds(-500)));
+  assert_eq!(unpack("0.000000001s"), Some(Duration::nanoseconds(1)));
+  assert_eq!(
+    unpack("315576000000s"),
+    Some(Duration::seconds(315_576_000_000))
+  );
+  for invalid in &[
+    "",
+    "s",
+    "1",
+    "1.s",
+    ".5s",
+    "--1s",
+    "+1s",
+    "1.0000000001s",
+    "315576000001s",
+  ] {
+    assert_eq!(unpack(invalid), None, "{}", invalid);
+  }
+
+  // Precision below the unit is dropped, timestamps round down and durations toward zero
+  let before_epoch =
+    Utc.ymd(1969, 12, 31).and_hms_milli(23, 59, 59, 999) + Duration::microseconds(500);
+  assert_eq!(TimestampMillis::pack(before_epoch).unwrap(), -1);
+  assert_eq!(TimestampSecs::pack(before_epoch).unwrap(), -1);
+  assert_eq!(
+    TimestampMillis::unpack(-1).unwrap(),
+    before_epoch - Duration::microseconds(500)
+  );
+  assert_eq!(
+    DurationSecs::pack(Duration::milliseconds(-1500)).unwrap(),
+    -1
+  );
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "chrono-tz")]
+
+use chrono::{DateTime, NaiveDate, TimeZone as _};
+use chrono_tz::Tz;
+use s2_grpc_utils::google_type::{date_time::TimeOffset, DateTime as ProtoDateTime, TimeZone};
+use s2_grpc_utils::{S2ProtoPack, S2ProtoUnpack};
+
+fn time_zone(id: &str) -> TimeZone {
+  TimeZone {
+    id: id.to_string(),
+    version: String::new(),
+  }
+}
+
+fn local(hours: i32, minutes: i32, id: &str) -> ProtoDateTime {
+  ProtoDateTime {
+    year: 2021,
+    month: 11,
+    day: 7,
+    hours,
+    minutes,
+    seconds: 0,
+    nanos: 0,
+    time_offset: Some(TimeOffset::TimeZone(time_zone(id))),
+  }
+}
+
+#[test]
+fn time_zone_id() {
+  let packed: TimeZone = chrono_tz::America::New_York.pack().unwrap();
+  assert_eq!(packed, time_zone("America/New_York"));
+  assert_eq!(Tz::unpack(packed).unwrap(), chrono_tz::America::New_York);
+  assert_eq!(
+    format!("{}", Tz::unpack(time_zone("Mars/Olympus")).err().unwrap()),
+    "Unknown time zone: Mars/Olympus"
+  );
+}
+
+#[test]
+fn zoned_date_time() {
+  let tz = chrono_tz::America::New_York;
+  let dt = tz
+    .from_local_datetime(&NaiveDate::from_ymd(2021, 11, 7).and_hms(12, 0, 0))
+    .unwrap();
+  let packed: ProtoDateTime = dt.pack().unwrap();
+  assert_eq!(packed, local(12, 0, "America/New_York"));
+  assert_eq!(DateTime::<Tz>::unpack(packed).unwrap(), dt);
+
+  // 01:30 happens twice when clocks are set back, the earlier one is in daylight saving time
+  let ambiguous = DateTime::<Tz>::unpack(local(1, 30, "America/New_York")).unwrap();
+  assert_eq!(ambiguous.timestamp(), 1_636_263_000);
+
+  let gap = ProtoDateTime {
+    month: 3,
+    day: 14,
+    ..local(2, 30, "America/New_York")
+  };
+  assert_eq!(
+    format!("{}", DateTime::<Tz>::unpack(gap).err().unwrap()),
+    "Invalid date time offset: local date time does not exist in the time zone"
+  );
+  assert_eq!(
+    format!(
+      "{}",
+      DateTime::<Tz>::unpack(local(1, 30, "Nowhere/Null"))
+        .err()
+        .unwrap()
+    ),
+    "Unknown time zone: Nowhere/Null"
+  );
+}
@@ -0,0 +1,8 @@
+#[test]
+fn compile_fail() {
+  let t = trybuild::TestCases::new();
+  t.compile_fail("tests/ui/*.rs");
+  // rustc lists the chrono calendar enums as candidate impls in these diagnostics
+  #[cfg(feature = "chrono")]
+  t.compile_fail("tests/ui/chrono/*.rs");
+}
@@ -0,0 +1,62 @@
+use prost_types::Timestamp;
+use s2_grpc_utils::{S2ProtoPack, S2ProtoUnpack};
+
+#[test]
+fn std_duration() {
+  let duration = std::time::Duration::new(5, 250);
+  let packed: prost_types::Duration = duration.pack().unwrap();
+  assert_eq!(
+    packed,
+    prost_types::Duration {
+      seconds: 5,
+      nanos: 250,
+    }
+  );
+  assert_eq!(std::time::Duration::unpack(packed).unwrap(), duration);
+  assert_eq!(
+    format!(
+      "{}",
+      std::time::Duration::unpack(prost_types::Duration {
+        seconds: -1,
+        nanos: 0,
+      })
+      .err()
+      .unwrap()
+    ),
+    "Invalid duration: seconds = -1, nanos = 0: negative durations can not be converted to std::time::Duration"
+  );
+  let packed: Result<prost_types::Duration, _> = std::time::Duration::from_secs(u64::MAX).pack();
+  assert!(packed.is_err());
+  assert_eq!(
+    Option::<std::time::Duration>::unpack(None::<prost_types::Duration>).unwrap(),
+    None
+  );
+}
+
+#[test]
+fn system_time() {
+  use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+  let cases = vec![
+    (UNIX_EPOCH + Duration::new(10, 5), 10, 5),
+    (UNIX_EPOCH - Duration::new(1, 250_000_000), -2, 750_000_000),
+  ];
+  for (time, seconds, nanos) in cases {
+    let packed: Timestamp = time.pack().unwrap();
+    assert_eq!(packed, Timestamp { seconds, nanos });
+    assert_eq!(SystemTime::unpack(packed).unwrap(), time);
+  }
+  assert!(SystemTime::unpack(Timestamp {
+    seconds: 253_402_300_800,
+    nanos: 0,
+  })
+  .is_err());
+  let packed: Option<Timestamp> = Some(UNIX_EPOCH).pack().unwrap();
+  assert_eq!(
+    packed,
+    Some(Timestamp {
+      seconds: 0,
+      nanos: 0,
+    })
+  );
+}
@@ -1,6 +1,9 @@
 use prost_types::value::Kind;
 use prost_types::{Struct, Value};
-use s2_grpc_utils::{S2ProtoEnum, S2ProtoPack, S2ProtoUnpack};
+use s2_grpc_utils::{
+  S2ProtoDiff, S2ProtoEnum, S2ProtoEnumVariants, S2ProtoPack, S2ProtoSchema, S2ProtoUnpack,
+  S2ProtoUnpackRef,
+};
 use serde_json::{json, Value as JsonValue};
 use std::collections::HashMap;
 
@@ -47,21 +50,12 @@ fn derive_enum() {
   use crate::S2ProtoEnum;
 
   #[derive(Debug, PartialEq)]
+  #[allow(clippy::upper_case_acronyms)]
   enum EnumProto {
     A = 0,
     BBBB = 1,
   }
 
-  impl EnumProto {
-    fn from_i32(v: i32) -> Option<Self> {
-      match v {
-        0 => Some(EnumProto::A),
-        1 => Some(EnumProto::BBBB),
-        _ => None,
-      }
-    }
-  }
-
   #[derive(Debug, S2ProtoEnum, PartialEq)]
   #[s2_grpc(proto_enum_type = "EnumProto")]
   enum EnumModel {
@@ -76,6 +70,616 @@ fn derive_enum() {
   assert_eq!(EnumModel::NAME, "EnumModel");
 }
 
+#[test]
+fn derive_enum_exhaustive() {
+  #[derive(Debug, PartialEq)]
+  enum StatusProto {
+    Active = 0,
+    Blocked = 1,
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(proto_enum_type = "StatusProto", exhaustive)]
+  enum Status {
+    Active,
+    #[s2_grpc(rename = "Blocked")]
+    Disabled,
+  }
+
+  assert_eq!(Status::from_i32(1), Some(Status::Disabled));
+  assert_eq!(Status::from_i32(2), None);
+  assert_eq!(S2ProtoEnum::pack(&Status::Active), StatusProto::Active);
+}
+
+#[test]
+fn derive_enum_alias() {
+  #[derive(Debug, PartialEq)]
+  enum StatusProto {
+    Pending = 0,
+    Canceled = 1,
+    Cancelled = 2,
+    Refunded = 3,
+  }
+
+  impl From<StatusProto> for i32 {
+    fn from(v: StatusProto) -> i32 {
+      v as i32
+    }
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(proto_enum_type = "StatusProto", exhaustive)]
+  enum Status {
+    Pending,
+    #[s2_grpc(rename = "Canceled", alias = "Cancelled", alias = "Refunded")]
+    Closed,
+  }
+
+  assert_eq!(Status::unpack(1).unwrap(), Status::Closed);
+  assert_eq!(Status::unpack(2).unwrap(), Status::Closed);
+  assert_eq!(Status::unpack(3).unwrap(), Status::Closed);
+  assert_eq!(Status::Closed.pack().unwrap(), 1);
+}
+
+#[test]
+fn derive_enum_string() {
+  #[derive(Debug, PartialEq)]
+  enum OrderStatus {
+    Pending = 0,
+    Shipped = 1,
+    PaymentPending = 2,
+    Refunded = 3,
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq, Eq, Hash)]
+  #[s2_grpc(proto_enum_type = "OrderStatus", string, case_insensitive)]
+  enum Status {
+    Pending,
+    #[s2_grpc(rename = "Shipped")]
+    Sent,
+    PaymentPending,
+    #[s2_grpc(proto_name = "REFUNDED")]
+    Refunded,
+  }
+
+  #[derive(Debug, PartialEq)]
+  struct OrderMessage {
+    status: String,
+    counts: HashMap<String, i32>,
+  }
+
+  #[derive(Debug, S2ProtoPack, S2ProtoUnpack, PartialEq)]
+  #[s2_grpc(message_type = "OrderMessage")]
+  struct Order {
+    status: Status,
+    counts: HashMap<Status, i32>,
+  }
+
+  let order = Order::unpack(OrderMessage {
+    status: "order_status_shipped".to_string(),
+    counts: vec![
+      ("ORDER_STATUS_PAYMENT_PENDING".to_string(), 1),
+      ("Refunded".to_string(), 2),
+    ]
+    .into_iter()
+    .collect(),
+  })
+  .unwrap();
+  assert_eq!(
+    order,
+    Order {
+      status: Status::Sent,
+      counts: vec![(Status::PaymentPending, 1), (Status::Refunded, 2)]
+        .into_iter()
+        .collect(),
+    }
+  );
+
+  let msg: OrderMessage = order.pack().unwrap();
+  assert_eq!(
+    msg,
+    OrderMessage {
+      status: "ORDER_STATUS_SHIPPED".to_string(),
+      counts: vec![
+        ("ORDER_STATUS_PAYMENT_PENDING".to_string(), 1),
+        ("REFUNDED".to_string(), 2),
+      ]
+      .into_iter()
+      .collect(),
+    }
+  );
+
+  assert_eq!(
+    Status::PaymentPending.get_proto_variant_name(),
+    Some("ORDER_STATUS_PAYMENT_PENDING")
+  );
+  assert_eq!(
+    Status::from_proto_variant_name("ORDER_STATUS_PENDING"),
+    Some(Status::Pending)
+  );
+  assert_eq!(
+    format!(
+      "{}",
+      Status::unpack("PaymentPending".to_string()).err().unwrap()
+    ),
+    "Enum variant name is not found: enum type = Status, name = PaymentPending"
+  );
+}
+
+#[test]
+fn derive_enum_unspecified() {
+  #[derive(Debug, PartialEq)]
+  enum StatusProto {
+    Unspecified = 0,
+    Active = 1,
+  }
+
+  impl From<StatusProto> for i32 {
+    fn from(v: StatusProto) -> i32 {
+      v as i32
+    }
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(
+    proto_enum_type = "StatusProto",
+    unspecified = "Unspecified",
+    exhaustive
+  )]
+  enum Status {
+    Active,
+  }
+
+  #[derive(Debug, PartialEq, Clone)]
+  struct AccountMessage {
+    status: i32,
+    previous_status: i32,
+  }
+
+  #[derive(Debug, S2ProtoPack, S2ProtoUnpack, PartialEq)]
+  #[s2_grpc(message_type = "AccountMessage")]
+  struct Account {
+    status: Status,
+    previous_status: Option<Status>,
+  }
+
+  let msg = AccountMessage {
+    status: 1,
+    previous_status: 0,
+  };
+  let account = Account::unpack(msg.clone()).unwrap();
+  assert_eq!(
+    account,
+    Account {
+      status: Status::Active,
+      previous_status: None,
+    }
+  );
+  let msg_: AccountMessage = account.pack().unwrap();
+  assert_eq!(msg_, msg);
+
+  assert_eq!(Option::<Status>::unpack(1).unwrap(), Some(Status::Active));
+  assert_eq!(
+    format!(
+      "{}",
+      Account::unpack(AccountMessage {
+        status: 0,
+        previous_status: 1,
+      })
+      .err()
+      .unwrap()
+    ),
+    "Could not unpack field 'status' from null"
+  );
+}
+
+#[test]
+fn derive_enum_discriminants() {
+  #[derive(Debug, S2ProtoEnum, S2ProtoSchema, PartialEq)]
+  enum ErrorCode {
+    NotFound = 404,
+    Conflict = 409,
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  enum Priority {
+    #[s2_grpc(value = 1)]
+    Low,
+    #[s2_grpc(value = 2)]
+    High,
+    #[s2_grpc(unknown)]
+    Other(i32),
+  }
+
+  assert_eq!(ErrorCode::from_i32(409), Some(ErrorCode::Conflict));
+  assert_eq!(ErrorCode::from_i32(500), None);
+  assert_eq!(S2ProtoEnum::pack(&ErrorCode::NotFound), 404);
+  assert_eq!(S2ProtoPack::<i32>::pack(ErrorCode::Conflict).unwrap(), 409);
+  assert_eq!(
+    <ErrorCode as S2ProtoUnpack<i32>>::unpack(404).unwrap(),
+    ErrorCode::NotFound
+  );
+  assert_eq!(
+    ErrorCode::proto_schema(),
+    s2_grpc_utils::schema::Schema::Enum(s2_grpc_utils::schema::EnumSchema {
+      name: "ErrorCode".to_string(),
+      values: vec![
+        s2_grpc_utils::schema::EnumValue {
+          name: "ERROR_CODE_NOT_FOUND".to_string(),
+          number: 404,
+        },
+        s2_grpc_utils::schema::EnumValue {
+          name: "ERROR_CODE_CONFLICT".to_string(),
+          number: 409,
+        },
+      ],
+    })
+  );
+
+  assert_eq!(Priority::from_i32(2), Some(Priority::High));
+  assert_eq!(Priority::from_i32(7), Some(Priority::Other(7)));
+  assert_eq!(Priority::Low.to_i32(), 1);
+  assert_eq!(Priority::Other(7).to_i32(), 7);
+}
+
+#[test]
+fn render_enum_zero_value() {
+  use s2_grpc_utils::schema::{render_proto, EnumSchema, EnumValue, Schema};
+
+  #[derive(S2ProtoEnum, S2ProtoSchema)]
+  #[allow(dead_code)]
+  enum ErrorCode {
+    NotFound = 404,
+    Conflict = 409,
+  }
+
+  let level = Schema::Enum(EnumSchema {
+    name: "Level".to_string(),
+    values: vec![
+      EnumValue {
+        name: "LEVEL_HIGH".to_string(),
+        number: 1,
+      },
+      EnumValue {
+        name: "LEVEL_LOW".to_string(),
+        number: 0,
+      },
+    ],
+  });
+
+  assert_eq!(
+    render_proto("", &[ErrorCode::proto_schema(), level]),
+    r#"syntax = "proto3";
+
+enum ErrorCode {
+  ERROR_CODE_UNSPECIFIED = 0;
+  ERROR_CODE_NOT_FOUND = 404;
+  ERROR_CODE_CONFLICT = 409;
+}
+
+enum Level {
+  LEVEL_LOW = 0;
+  LEVEL_HIGH = 1;
+}
+"#
+  );
+}
+
+#[test]
+fn derive_enum_implicit_discriminants() {
+  const BASE: isize = 10;
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  enum Level {
+    Trace,
+    Debug,
+    Warn = 5,
+    Error,
+    Fatal = BASE,
+    Panic,
+  }
+
+  assert_eq!(
+    Level::variants(),
+    vec![
+      (&Level::Trace, 0),
+      (&Level::Debug, 1),
+      (&Level::Warn, 5),
+      (&Level::Error, 6),
+      (&Level::Fatal, 10),
+      (&Level::Panic, 11),
+    ]
+  );
+  assert_eq!(Level::from_i32(6), Some(Level::Error));
+  assert_eq!(Level::from_i32(11), Some(Level::Panic));
+}
+
+#[test]
+fn derive_enum_names() {
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(display = "proto", from_str = "rust", serde = "proto")]
+  enum Color {
+    #[s2_grpc(value = 1, rename = "COLOR_RED")]
+    Red,
+    #[s2_grpc(value = 2, rename = "COLOR_GREEN")]
+    Green,
+    #[s2_grpc(unknown)]
+    Other(i32),
+  }
+
+  assert_eq!(Color::ALL, &[Color::Red, Color::Green]);
+  assert_eq!(
+    Color::variants(),
+    vec![(&Color::Red, 1), (&Color::Green, 2)]
+  );
+  assert_eq!(Color::from_variant_name("Green"), Some(Color::Green));
+  assert_eq!(Color::from_variant_name("COLOR_GREEN"), None);
+  assert_eq!(Color::Green.get_proto_variant_name(), Some("COLOR_GREEN"));
+
+  assert_eq!(Color::Red.to_string(), "COLOR_RED");
+  assert_eq!(Color::Other(7).to_string(), "7");
+  assert_eq!("Red".parse::<Color>().unwrap(), Color::Red);
+  assert_eq!(
+    format!("{}", "COLOR_RED".parse::<Color>().err().unwrap()),
+    "Enum variant name is not found: enum type = Color, name = COLOR_RED"
+  );
+
+  assert_eq!(
+    serde_json::to_value(vec![Color::Green, Color::Other(7)]).unwrap(),
+    json!(["COLOR_GREEN", 7])
+  );
+  assert_eq!(
+    serde_json::from_value::<Vec<Color>>(json!(["COLOR_RED", 9])).unwrap(),
+    vec![Color::Red, Color::Other(9)]
+  );
+  assert!(serde_json::from_value::<Color>(json!("Red")).is_err());
+
+  let colors = vec![Color::Green, Color::Other(7)];
+  let bytes = bincode::serialize(&colors).unwrap();
+  assert_eq!(bincode::deserialize::<Vec<Color>>(&bytes).unwrap(), colors);
+}
+
+#[test]
+fn manual_enum_impl() {
+  #[derive(Debug, PartialEq)]
+  enum Level {
+    Low,
+    High,
+  }
+
+  impl S2ProtoEnum for Level {
+    type ProtoEnum = i32;
+    const NAME: &'static str = "Level";
+
+    fn from_i32(v: i32) -> Option<Self> {
+      match v {
+        1 => Some(Level::Low),
+        2 => Some(Level::High),
+        _ => None,
+      }
+    }
+
+    fn pack(&self) -> i32 {
+      match *self {
+        Level::Low => 1,
+        Level::High => 2,
+      }
+    }
+
+    fn get_variant_name(&self) -> &'static str {
+      match *self {
+        Level::Low => "Low",
+        Level::High => "High",
+      }
+    }
+  }
+
+  assert_eq!(
+    <Level as S2ProtoUnpack<i32>>::unpack(2).unwrap(),
+    Level::High
+  );
+  assert_eq!(Level::Low.to_i32(), 1);
+  assert_eq!(Level::from_variant_name("Low"), None);
+  assert_eq!(Level::Low.get_proto_variant_name(), None);
+}
+
+#[test]
+fn derive_enum_typed() {
+  use std::convert::TryFrom;
+
+  #[derive(Debug, PartialEq, Clone, Copy)]
+  enum StatusProto {
+    Unspecified = 0,
+    Active = 1,
+    Blocked = 2,
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(proto_enum_type = "StatusProto", unspecified = "Unspecified", typed)]
+  enum Status {
+    Active,
+    Blocked,
+  }
+
+  assert_eq!(StatusProto::from(Status::Blocked), StatusProto::Blocked);
+  assert_eq!(
+    Status::try_from(StatusProto::Active).unwrap(),
+    Status::Active
+  );
+  assert_eq!(
+    format!(
+      "{}",
+      Status::try_from(StatusProto::Unspecified).err().unwrap()
+    ),
+    "Could not unpack a non-optional value from null"
+  );
+
+  let proto: StatusProto = Status::Active.pack().unwrap();
+  assert_eq!(proto, StatusProto::Active);
+  assert_eq!(
+    <Status as S2ProtoUnpack<StatusProto>>::unpack(StatusProto::Blocked).unwrap(),
+    Status::Blocked
+  );
+}
+
+#[test]
+fn derive_enum_typed_unknown() {
+  use std::convert::TryFrom;
+
+  #[derive(Debug, PartialEq, Default)]
+  enum StatusProto {
+    #[default]
+    Unspecified = 0,
+    Active = 1,
+    Blocked = 2,
+  }
+
+  impl TryFrom<i32> for StatusProto {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+      match v {
+        0 => Ok(StatusProto::Unspecified),
+        1 => Ok(StatusProto::Active),
+        2 => Ok(StatusProto::Blocked),
+        _ => Err(v),
+      }
+    }
+  }
+
+  impl From<StatusProto> for i32 {
+    fn from(v: StatusProto) -> i32 {
+      v as i32
+    }
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(proto_enum_type = "StatusProto", typed)]
+  enum Status {
+    Unspecified,
+    Active,
+    #[s2_grpc(unknown)]
+    Unrecognized(i32),
+  }
+
+  assert_eq!(
+    StatusProto::try_from(Status::Active).unwrap(),
+    StatusProto::Active
+  );
+  assert_eq!(
+    StatusProto::try_from(Status::Unrecognized(2)).unwrap(),
+    StatusProto::Blocked
+  );
+  assert_eq!(
+    format!(
+      "{}",
+      StatusProto::try_from(Status::Unrecognized(3))
+        .err()
+        .unwrap()
+    ),
+    "Enum discriminant is not found: enum type = Status, discriminant = 3"
+  );
+
+  let proto: StatusProto = Status::Unrecognized(2).pack().unwrap();
+  assert_eq!(proto, StatusProto::Blocked);
+  assert_eq!(
+    <Status as S2ProtoUnpack<StatusProto>>::unpack(StatusProto::Blocked).unwrap(),
+    Status::Unrecognized(2)
+  );
+}
+
+#[test]
+fn derive_enum_unknown() {
+  use std::convert::TryFrom;
+
+  #[derive(Debug, PartialEq, Default)]
+  enum StatusProto {
+    #[default]
+    Unspecified = 0,
+    Active = 1,
+    Blocked = 2,
+  }
+
+  impl TryFrom<i32> for StatusProto {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+      match v {
+        0 => Ok(StatusProto::Unspecified),
+        1 => Ok(StatusProto::Active),
+        2 => Ok(StatusProto::Blocked),
+        _ => Err(v),
+      }
+    }
+  }
+
+  impl From<StatusProto> for i32 {
+    fn from(v: StatusProto) -> i32 {
+      v as i32
+    }
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(proto_enum_type = "StatusProto")]
+  enum Status {
+    Unspecified,
+    Active,
+    #[s2_grpc(unknown)]
+    Unrecognized(i32),
+  }
+
+  assert_eq!(Status::unpack(1).unwrap(), Status::Active);
+  assert_eq!(Status::unpack(2).unwrap(), Status::Unrecognized(2));
+  assert_eq!(Status::unpack(3).unwrap(), Status::Unrecognized(3));
+  assert_eq!(Status::Unrecognized(3).pack().unwrap(), 3);
+  assert_eq!(Status::Active.pack().unwrap(), 1);
+  assert_eq!(
+    S2ProtoEnum::pack(&Status::Unrecognized(2)),
+    StatusProto::Blocked
+  );
+  assert_eq!(
+    S2ProtoEnum::pack(&Status::Unrecognized(3)),
+    StatusProto::Unspecified
+  );
+  assert_eq!(Status::Unrecognized(3).get_variant_name(), "Unrecognized");
+}
+
+#[test]
+fn derive_enum_fallback() {
+  use std::sync::atomic::{AtomicI32, Ordering};
+
+  static FALLBACK_DISCRIMINANT: AtomicI32 = AtomicI32::new(-1);
+
+  fn report_fallback(enum_name: &'static str, discriminant: i32) {
+    assert_eq!(enum_name, "Status");
+    FALLBACK_DISCRIMINANT.store(discriminant, Ordering::SeqCst);
+  }
+
+  #[derive(Debug, PartialEq)]
+  #[allow(dead_code)]
+  enum StatusProto {
+    Unspecified = 0,
+    Active = 1,
+    Cancelled = 2,
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(proto_enum_type = "StatusProto", on_fallback = "report_fallback")]
+  enum Status {
+    #[s2_grpc(fallback)]
+    Unspecified,
+    Active,
+  }
+
+  assert_eq!(Status::unpack(1).unwrap(), Status::Active);
+  assert_eq!(Status::unpack(0).unwrap(), Status::Unspecified);
+  assert_eq!(FALLBACK_DISCRIMINANT.load(Ordering::SeqCst), -1);
+  assert_eq!(Status::unpack(2).unwrap(), Status::Unspecified);
+  assert_eq!(FALLBACK_DISCRIMINANT.load(Ordering::SeqCst), 2);
+  assert_eq!(Status::unpack(100).unwrap(), Status::Unspecified);
+  assert_eq!(FALLBACK_DISCRIMINANT.load(Ordering::SeqCst), 100);
+}
+
 #[test]
 fn derive() {
   let mut map = HashMap::new();
@@ -141,3 +745,559 @@ fn derive_err() {
     "Could not unpack field 'json' from null"
   )
 }
+
+#[test]
+fn derive_diff() {
+  #[derive(Debug, S2ProtoDiff, PartialEq, Clone)]
+  #[s2_grpc(message_type = "DiffMessage")]
+  struct DiffModel {
+    #[s2_grpc(rename = "display_name")]
+    name: String,
+    nested: Option<NestedDiffModel>,
+    tags: Vec<String>,
+  }
+
+  #[derive(Debug, S2ProtoDiff, PartialEq, Clone)]
+  #[s2_grpc(message_type = "NestedDiffMessage")]
+  struct NestedDiffModel {
+    a: i32,
+    b: Option<String>,
+  }
+
+  let old = DiffModel {
+    name: "old".to_string(),
+    nested: Some(NestedDiffModel { a: 1, b: None }),
+    tags: vec![],
+  };
+
+  assert!(old.diff_mask(&old).paths.is_empty());
+
+  let new = DiffModel {
+    name: "new".to_string(),
+    nested: Some(NestedDiffModel {
+      a: 1,
+      b: Some("b".to_string()),
+    }),
+    tags: vec!["tag".to_string()],
+  };
+  assert_eq!(
+    old.diff_mask(&new).paths,
+    vec!["display_name", "nested.b", "tags"]
+  );
+
+  let removed = DiffModel {
+    nested: None,
+    ..old.clone()
+  };
+  assert_eq!(old.diff_mask(&removed).paths, vec!["nested"]);
+}
+
+#[test]
+fn derive_diff_json() {
+  use s2_grpc_utils::Json;
+
+  #[derive(S2ProtoDiff)]
+  #[s2_grpc(message_type = "JsonMessage")]
+  struct JsonModel {
+    name: String,
+    data: Json<Vec<i32>>,
+  }
+
+  let old = JsonModel {
+    name: "name".to_string(),
+    data: Json(vec![1]),
+  };
+  let new = JsonModel {
+    name: "name".to_string(),
+    data: Json(vec![1, 2]),
+  };
+  assert!(old.diff_mask(&old).paths.is_empty());
+  assert_eq!(old.diff_mask(&new).paths, vec!["data"]);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn derive_schema() {
+  use chrono::{DateTime, Utc};
+
+  #[allow(dead_code)]
+  enum Status {
+    Active = 0,
+    Blocked = 1,
+  }
+
+  #[derive(S2ProtoSchema)]
+  #[s2_grpc(proto_enum_type = "Status")]
+  #[allow(dead_code)]
+  enum UserStatus {
+    Active,
+    Blocked,
+  }
+
+  #[derive(S2ProtoSchema)]
+  #[s2_grpc(message_type = "proto::User")]
+  #[allow(dead_code)]
+  struct User {
+    id: i64,
+    #[s2_grpc(rename = "display_name")]
+    name: Option<String>,
+    status: UserStatus,
+    created_at: DateTime<Utc>,
+    data: JsonValue,
+    tags: Vec<String>,
+    groups: HashMap<String, Group>,
+  }
+
+  #[derive(S2ProtoSchema)]
+  #[s2_grpc(message_type = "proto::Group")]
+  #[allow(dead_code)]
+  struct Group {
+    name: String,
+  }
+
+  assert_eq!(
+    s2_grpc_utils::schema::render_proto(
+      "shop",
+      &[
+        User::proto_schema(),
+        Group::proto_schema(),
+        UserStatus::proto_schema()
+      ]
+    ),
+    r#"syntax = "proto3";
+
+package shop;
+
+import "google/protobuf/struct.proto";
+import "google/protobuf/timestamp.proto";
+import "google/protobuf/wrappers.proto";
+
+message User {
+  int64 id = 1;
+  google.protobuf.StringValue display_name = 2;
+  Status status = 3;
+  google.protobuf.Timestamp created_at = 4;
+  google.protobuf.Value data = 5;
+  repeated string tags = 6;
+  map<string, Group> groups = 7;
+}
+
+message Group {
+  string name = 1;
+}
+
+enum Status {
+  STATUS_ACTIVE = 0;
+  STATUS_BLOCKED = 1;
+}
+"#
+  );
+}
+
+#[test]
+fn derive_unpack_ref() {
+  use std::borrow::Cow;
+
+  #[derive(Debug, Clone)]
+  struct ViewMessage {
+    name: String,
+    tags: Vec<String>,
+    data: Vec<u8>,
+    nested: Option<NestedMessage>,
+    count: i32,
+  }
+
+  #[derive(Debug, S2ProtoUnpackRef, PartialEq)]
+  #[s2_grpc(message_type = "ViewMessage")]
+  struct View<'a> {
+    name: &'a str,
+    tags: Vec<Cow<'a, str>>,
+    data: &'a [u8],
+    nested: Option<&'a NestedMessage>,
+    count: i32,
+  }
+
+  #[derive(Debug, S2ProtoUnpackRef, PartialEq)]
+  #[s2_grpc(message_type = "NestedMessage")]
+  struct NestedView {
+    v: i32,
+  }
+
+  #[derive(Debug, S2ProtoUnpackRef, PartialEq)]
+  #[s2_grpc(message_type = "ViewMessage")]
+  struct OuterView<'a> {
+    #[s2_grpc(rename = "name")]
+    title: &'a str,
+    nested: NestedView,
+  }
+
+  let msg = ViewMessage {
+    name: "name".to_string(),
+    tags: vec!["a".to_string(), "b".to_string()],
+    data: vec![1, 2, 3],
+    nested: Some(NestedMessage { v: 1 }),
+    count: 2,
+  };
+
+  let view = View::unpack_ref(&msg).unwrap();
+  assert_eq!(
+    view,
+    View {
+      name: "name",
+      tags: vec![Cow::Borrowed("a"), Cow::Borrowed("b")],
+      data: &[1, 2, 3],
+      nested: Some(&NestedMessage { v: 1 }),
+      count: 2,
+    }
+  );
+  assert_eq!(view.name.as_ptr(), msg.name.as_ptr());
+
+  assert_eq!(
+    OuterView::unpack_ref(&msg).unwrap(),
+    OuterView {
+      title: "name",
+      nested: NestedView { v: 1 },
+    }
+  );
+
+  let msg = ViewMessage {
+    nested: None,
+    ..msg
+  };
+  assert_eq!(
+    format!("{}", OuterView::unpack_ref(&msg).err().unwrap()),
+    "Could not unpack field 'nested' from null"
+  );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn derive_unpack_ref_converted() {
+  use chrono::{DateTime, Duration, TimeZone, Utc};
+  use prost_types::Timestamp;
+
+  #[derive(Debug, PartialEq)]
+  enum StatusProto {
+    Active = 0,
+    Blocked = 1,
+  }
+
+  impl From<StatusProto> for i32 {
+    fn from(v: StatusProto) -> i32 {
+      v as i32
+    }
+  }
+
+  #[derive(Debug, S2ProtoEnum, PartialEq)]
+  #[s2_grpc(proto_enum_type = "StatusProto")]
+  enum Status {
+    Active,
+    Blocked,
+  }
+
+  struct EventMessage {
+    name: String,
+    created_at: Option<Timestamp>,
+    timeout: Option<prost_types::Duration>,
+    status: i32,
+  }
+
+  #[derive(Debug, S2ProtoUnpackRef, PartialEq)]
+  #[s2_grpc(message_type = "EventMessage")]
+  struct EventView<'a> {
+    name: &'a str,
+    created_at: DateTime<Utc>,
+    timeout: Option<Duration>,
+    status: Status,
+  }
+
+  let msg = EventMessage {
+    name: "event".to_string(),
+    created_at: Some(Timestamp {
+      seconds: 1_614_902_400,
+      nanos: 0,
+    }),
+    timeout: None,
+    status: 1,
+  };
+  assert_eq!(
+    EventView::unpack_ref(&msg).unwrap(),
+    EventView {
+      name: "event",
+      created_at: Utc.ymd(2021, 3, 5).and_hms(0, 0, 0),
+      timeout: None,
+      status: Status::Blocked,
+    }
+  );
+}
+
+mod facade {
+  pub use s2_grpc_utils as grpc;
+}
+
+mod reexported {
+  use super::facade::grpc;
+
+  pub struct Message {
+    pub v: i32,
+    pub status: i32,
+  }
+
+  #[derive(Debug, PartialEq)]
+  pub enum Status {
+    A = 0,
+  }
+
+  impl From<Status> for i32 {
+    fn from(v: Status) -> i32 {
+      v as i32
+    }
+  }
+
+  #[derive(Debug, PartialEq, grpc::S2ProtoEnum)]
+  #[s2_grpc(crate = "crate::facade::grpc", proto_enum_type = "Status")]
+  pub enum StatusModel {
+    A,
+  }
+
+  #[derive(Debug, PartialEq, grpc::S2ProtoPack, grpc::S2ProtoUnpack)]
+  #[s2_grpc(crate = "crate::facade::grpc", message_type = "Message")]
+  pub struct Model {
+    pub v: i32,
+    pub status: StatusModel,
+  }
+}
+
+mod shadowed_to_string {
+  #![allow(dead_code)]
+
+  use s2_grpc_utils::{S2ProtoEnum, S2ProtoSchema};
+  use std::convert::TryFrom;
+
+  // Generated code must not resolve `ToString` from the prelude
+  trait ToString {}
+
+  #[derive(Default)]
+  pub enum Status {
+    #[default]
+    Active = 0,
+    Blocked = 1,
+  }
+
+  impl TryFrom<i32> for Status {
+    type Error = i32;
+
+    fn try_from(v: i32) -> Result<Self, i32> {
+      match v {
+        0 => Ok(Status::Active),
+        1 => Ok(Status::Blocked),
+        _ => Err(v),
+      }
+    }
+  }
+
+  impl From<Status> for i32 {
+    fn from(v: Status) -> i32 {
+      v as i32
+    }
+  }
+
+  #[derive(S2ProtoEnum, S2ProtoSchema)]
+  #[s2_grpc(proto_enum_type = "Status", string, from_str = "rust", serde = "proto")]
+  pub enum StatusModel {
+    Active,
+    Blocked,
+    #[s2_grpc(unknown)]
+    Unrecognized(i32),
+  }
+}
+
+#[test]
+fn derive_crate_path() {
+  use reexported::{Message, Model, StatusModel};
+
+  let model = Model::unpack(Message { v: 1, status: 0 }).unwrap();
+  assert_eq!(
+    model,
+    Model {
+      v: 1,
+      status: StatusModel::A
+    }
+  );
+  let msg: Message = model.pack().unwrap();
+  assert_eq!((msg.v, msg.status), (1, 0));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn derive_encoding() {
+  use chrono::{DateTime, Duration, TimeZone, Utc};
+
+  #[derive(Debug, Clone, PartialEq)]
+  struct LegacyMessage {
+    created_at: i64,
+    updated_at: String,
+    expires_at: i64,
+    timeout: String,
+    interval: i64,
+  }
+
+  #[derive(Debug, S2ProtoPack, S2ProtoUnpack, S2ProtoUnpackRef, S2ProtoSchema, PartialEq)]
+  #[s2_grpc(message_type = "LegacyMessage")]
+  struct Legacy {
+    #[s2_grpc(timestamp = "millis")]
+    created_at: DateTime<Utc>,
+    #[s2_grpc(timestamp = "rfc3339")]
+    updated_at: DateTime<Utc>,
+    #[s2_grpc(timestamp = "secs")]
+    expires_at: DateTime<Utc>,
+    #[s2_grpc(duration = "string")]
+    timeout: Duration,
+    #[s2_grpc(duration = "millis")]
+    interval: Duration,
+  }
+
+  let model = Legacy {
+    created_at: Utc.ymd(2021, 3, 4).and_hms_milli(10, 20, 30, 400),
+    updated_at: Utc.ymd(2021, 3, 4).and_hms_milli(10, 20, 30, 500),
+    expires_at: Utc.ymd(2021, 3, 5).and_hms(0, 0, 0),
+    timeout: Duration::milliseconds(1500),
+    interval: Duration::milliseconds(-250),
+  };
+  let msg = LegacyMessage {
+    created_at: 1_614_853_230_400,
+    updated_at: "2021-03-04T10:20:30.500Z".to_string(),
+    expires_at: 1_614_902_400,
+    timeout: "1.500s".to_string(),
+    interval: -250,
+  };
+
+  let packed: LegacyMessage = model.pack().unwrap();
+  assert_eq!(packed, msg);
+  assert_eq!(
+    Legacy::unpack_ref(&msg).unwrap(),
+    Legacy::unpack(msg.clone()).unwrap()
+  );
+
+  let unpacked = Legacy::unpack(LegacyMessage {
+    updated_at: "2021-03-04T12:20:30.5+02:00".to_string(),
+    timeout: "1.5s".to_string(),
+    ..msg.clone()
+  })
+  .unwrap();
+  assert_eq!(unpacked, Legacy::unpack(msg.clone()).unwrap());
+
+  let err = Legacy::unpack(LegacyMessage {
+    updated_at: "yesterday".to_string(),
+    ..msg.clone()
+  })
+  .err()
+  .unwrap();
+  // The rest of the message comes from chrono and changes between versions
+  assert!(
+    format!("{}", err).starts_with("Field 'updated_at': Parse timestamp error: 'yesterday': ")
+  );
+  let err = Legacy::unpack(LegacyMessage {
+    timeout: "1.5".to_string(),
+    ..msg.clone()
+  })
+  .err()
+  .unwrap();
+  assert_eq!(
+    format!("{}", err),
+    "Field 'timeout': Parse duration error: '1.5' is not a number of seconds like \"1.5s\""
+  );
+  let err = Legacy::unpack(LegacyMessage {
+    expires_at: i64::MAX,
+    ..msg
+  })
+  .err()
+  .unwrap();
+  assert!(format!("{}", err).starts_with("Field 'expires_at': Invalid timestamp"));
+
+  match Legacy::proto_schema() {
+    s2_grpc_utils::schema::Schema::Message(schema) => {
+      let types: Vec<_> = schema.fields.iter().map(|f| f.ty.clone()).collect();
+      assert_eq!(
+        types,
+        vec![
+          s2_grpc_utils::schema::FieldType::Scalar("int64"),
+          s2_grpc_utils::schema::FieldType::Scalar("string"),
+          s2_grpc_utils::schema::FieldType::Scalar("int64"),
+          s2_grpc_utils::schema::FieldType::Scalar("string"),
+          s2_grpc_utils::schema::FieldType::Scalar("int64"),
+        ]
+      );
+    }
+    _ => unreachable!(),
+  }
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn derive_encoding_option() {
+  use chrono::{DateTime, Duration, TimeZone, Utc};
+
+  #[derive(Debug, Clone, PartialEq)]
+  struct LegacyMessage {
+    deleted_at: i64,
+    updated_at: String,
+    timeout: String,
+  }
+
+  #[derive(Debug, S2ProtoPack, S2ProtoUnpack, S2ProtoUnpackRef, PartialEq)]
+  #[s2_grpc(message_type = "LegacyMessage")]
+  struct Legacy {
+    #[s2_grpc(timestamp = "secs")]
+    deleted_at: Option<DateTime<Utc>>,
+    #[s2_grpc(timestamp = "rfc3339")]
+    updated_at: Option<DateTime<Utc>>,
+    #[s2_grpc(duration = "string")]
+    timeout: Option<Duration>,
+  }
+
+  let empty = LegacyMessage {
+    deleted_at: 0,
+    updated_at: String::new(),
+    timeout: String::new(),
+  };
+  let model = Legacy::unpack(empty.clone()).unwrap();
+  assert_eq!(
+    model,
+    Legacy {
+      deleted_at: None,
+      updated_at: None,
+      timeout: None,
+    }
+  );
+  let packed: LegacyMessage = model.pack().unwrap();
+  assert_eq!(packed, empty);
+
+  let msg = LegacyMessage {
+    deleted_at: 1_614_902_400,
+    updated_at: "2021-03-04T10:20:30Z".to_string(),
+    timeout: "1.500s".to_string(),
+  };
+  let model = Legacy::unpack_ref(&msg).unwrap();
+  assert_eq!(
+    model,
+    Legacy {
+      deleted_at: Some(Utc.ymd(2021, 3, 5).and_hms(0, 0, 0)),
+      updated_at: Some(Utc.ymd(2021, 3, 4).and_hms(10, 20, 30)),
+      timeout: Some(Duration::milliseconds(1500)),
+    }
+  );
+  let packed: LegacyMessage = model.pack().unwrap();
+  assert_eq!(packed, msg);
+
+  let err = Legacy::unpack(LegacyMessage {
+    timeout: "soon".to_string(),
+    ..msg
+  })
+  .err()
+  .unwrap();
+  assert_eq!(
+    format!("{}", err),
+    "Field 'timeout': Parse duration error: 'soon' is not a number of seconds like \"1.5s\""
+  );
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "time")]
+
+use prost_types::Timestamp;
+use s2_grpc_utils::google_type::{Date, TimeOfDay};
+use s2_grpc_utils::{S2ProtoPack, S2ProtoUnpack};
+use time::{Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+#[test]
+fn time_timestamp() {
+  let dt = time::Date::from_calendar_date(2020, Month::February, 29)
+    .unwrap()
+    .with_hms_nano(12, 30, 0, 5)
+    .unwrap();
+  let timestamp = Timestamp {
+    seconds: 1_582_979_400,
+    nanos: 5,
+  };
+
+  let packed: Timestamp = dt.pack().unwrap();
+  assert_eq!(packed, timestamp);
+  assert_eq!(PrimitiveDateTime::unpack(timestamp.clone()).unwrap(), dt);
+  assert_eq!(OffsetDateTime::unpack(timestamp).unwrap(), dt.assume_utc());
+  assert!(OffsetDateTime::unpack(Timestamp {
+    seconds: 253_402_300_800,
+    nanos: 0,
+  })
+  .is_err());
+}
+
+#[test]
+fn time_duration() {
+  let duration = time::Duration::new(-3, -500);
+  let packed: prost_types::Duration = duration.pack().unwrap();
+  assert_eq!(
+    packed,
+    prost_types::Duration {
+      seconds: -3,
+      nanos: -500,
+    }
+  );
+  assert_eq!(time::Duration::unpack(packed).unwrap(), duration);
+}
+
+#[test]
+fn time_date() {
+  let date = time::Date::from_calendar_date(2021, Month::March, 4).unwrap();
+  let packed: Date = date.pack().unwrap();
+  assert_eq!(
+    packed,
+    Date {
+      year: 2021,
+      month: 3,
+      day: 4,
+    }
+  );
+  assert_eq!(time::Date::unpack(packed).unwrap(), date);
+  assert_eq!(Option::<time::Date>::unpack(None::<Date>).unwrap(), None);
+
+  let message = |year, month, day| {
+    format!(
+      "{}",
+      time::Date::unpack(Date { year, month, day }).err().unwrap()
+    )
+  };
+  assert_eq!(
+    message(2021, 2, 29),
+    "Invalid date: year = 2021, month = 2, day = 29: invalid calendar date"
+  );
+  assert_eq!(
+    message(2021, 0, 0),
+    "Invalid date: year = 2021, month = 0, day = 0: partial dates are not supported"
+  );
+}
+
+#[test]
+fn time_time_of_day() {
+  let time = Time::from_hms_nano(23, 59, 58, 7).unwrap();
+  let packed: TimeOfDay = time.pack().unwrap();
+  assert_eq!(
+    packed,
+    TimeOfDay {
+      hours: 23,
+      minutes: 59,
+      seconds: 58,
+      nanos: 7,
+    }
+  );
+  assert_eq!(Time::unpack(packed).unwrap(), time);
+
+  let message = |hours, minutes, seconds| {
+    format!(
+      "{}",
+      Time::unpack(TimeOfDay {
+        hours,
+        minutes,
+        seconds,
+        nanos: 0,
+      })
+      .err()
+      .unwrap()
+    )
+  };
+  assert_eq!(
+    message(24, 0, 0),
+    "Invalid time of day: hours = 24, minutes = 0, seconds = 0, nanos = 0: 24:00:00 can not be represented by time::Time"
+  );
+  assert_eq!(
+    message(24, 0, 1),
+    "Invalid time of day: hours = 24, minutes = 0, seconds = 1, nanos = 0: hours can only be 24 for 24:00:00"
+  );
+  assert_eq!(
+    message(10, 60, 0),
+    "Invalid time of day: hours = 10, minutes = 60, seconds = 0, nanos = 0: minutes out of range 0 to 59"
+  );
+}
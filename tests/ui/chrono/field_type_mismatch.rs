@@ -0,0 +1,17 @@
+use s2_grpc_utils::{S2ProtoPack, S2ProtoUnpack};
+
+struct UserId(i32);
+
+struct UserMessage {
+  id: i32,
+  name: String,
+}
+
+#[derive(S2ProtoPack, S2ProtoUnpack)]
+#[s2_grpc(message_type = "UserMessage")]
+struct User {
+  id: UserId,
+  name: String,
+}
+
+fn main() {}
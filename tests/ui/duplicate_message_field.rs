@@ -0,0 +1,28 @@
+use s2_grpc_utils::{S2ProtoPack, S2ProtoUnpack};
+
+struct Message {
+  name: String,
+}
+
+#[derive(S2ProtoPack)]
+#[s2_grpc(message_type = "Message")]
+struct Renamed {
+  name: String,
+  #[s2_grpc(rename = "name")]
+  display_name: String,
+}
+
+fn map_name(v: String) -> String {
+  v
+}
+
+#[derive(S2ProtoUnpack)]
+#[s2_grpc(message_type = "Message")]
+struct Mapped {
+  #[s2_grpc(map_fn = "map_name")]
+  name: String,
+  #[s2_grpc(map_fn = "map_name", rename = "name")]
+  other_name: String,
+}
+
+fn main() {}
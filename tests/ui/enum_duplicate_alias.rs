@@ -0,0 +1,29 @@
+use s2_grpc_utils::S2ProtoEnum;
+
+#[allow(dead_code)]
+enum StatusProto {
+  Pending = 0,
+  Canceled = 1,
+  Cancelled = 2,
+  Refunded = 3,
+}
+
+#[derive(S2ProtoEnum)]
+#[s2_grpc(proto_enum_type = "StatusProto")]
+enum Status {
+  Pending,
+  #[s2_grpc(alias = "Cancelled")]
+  Canceled,
+  #[s2_grpc(alias = "Cancelled")]
+  Refunded,
+}
+
+#[derive(S2ProtoEnum)]
+#[s2_grpc(proto_enum_type = "StatusProto")]
+enum Renamed {
+  Pending,
+  #[s2_grpc(rename = "Pending")]
+  Waiting,
+}
+
+fn main() {}
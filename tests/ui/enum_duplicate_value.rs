@@ -0,0 +1,21 @@
+use s2_grpc_utils::S2ProtoEnum;
+
+#[derive(S2ProtoEnum)]
+enum Priority {
+  #[s2_grpc(value = 1)]
+  Low,
+  #[s2_grpc(value = 1)]
+  High,
+  #[s2_grpc(unknown)]
+  Other(i32),
+}
+
+#[derive(S2ProtoEnum)]
+enum ErrorCode {
+  NotFound = 404,
+  Conflict,
+  #[s2_grpc(value = 405)]
+  Gone,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use s2_grpc_utils::S2ProtoEnum;
+
+#[allow(dead_code)]
+enum StatusProto {
+  Active = 0,
+  Blocked = 1,
+  Deleted = 2,
+}
+
+#[derive(S2ProtoEnum)]
+#[s2_grpc(proto_enum_type = "StatusProto", exhaustive)]
+enum Status {
+  Active,
+  Blocked,
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+use s2_grpc_utils::{S2ProtoEnum, S2ProtoPack, S2ProtoUnpack};
+
+#[allow(dead_code)]
+enum StatusProto {
+  Active = 0,
+  Blocked = 1,
+}
+
+impl From<StatusProto> for i32 {
+  fn from(v: StatusProto) -> i32 {
+    v as i32
+  }
+}
+
+#[derive(S2ProtoEnum)]
+#[s2_grpc(proto_enum_type = "StatusProto")]
+enum Status {
+  Active,
+  Blocked,
+}
+
+struct AccountMessage {
+  status: i32,
+}
+
+#[derive(S2ProtoPack, S2ProtoUnpack)]
+#[s2_grpc(message_type = "AccountMessage")]
+struct Account {
+  status: Option<Status>,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use s2_grpc_utils::S2ProtoPack;
+
+struct Message {
+  name: String,
+}
+
+#[derive(S2ProtoPack)]
+#[s2_grpc(message_type = "Message")]
+struct Model {
+  #[s2_grpc(renam = "name")]
+  display_name: String,
+}
+
+fn main() {}